@@ -45,7 +45,14 @@ select id, uuid, name, genre, bio, data, attrs, ip_inet, ip_cidr, mac_address, g
 from authors
 order by name
 "#;
-#[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    postgres_derive::ToSql,
+    postgres_derive::FromSql,
+    sqlc_core::FromPostgresRow
+)]
 #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "hash", derive(Eq, Hash))]
 #[postgres(name = "type_genre")]
@@ -113,19 +120,52 @@ pub(crate) struct CreateAuthorParams {
     pub bio: Option<String>,
 }
 pub(crate) async fn create_author(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
     arg: CreateAuthorParams,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(CREATE_AUTHOR, &[&arg.name, &arg.bio]).await?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(CREATE_AUTHOR, &[sqlc_core::Type::TEXT, sqlc_core::Type::TEXT])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client
+        .query_opt(&stmt, &[&arg.name, &arg.bio])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) async fn create_author_full(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
     arg: CreateAuthorFullParams,
 ) -> sqlc_core::Result<Author> {
-    let row = client
-        .query_one(
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR_FULL", ":one");
+    let stmt = client
+        .prepare_typed(
             CREATE_AUTHOR_FULL,
+            &[
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::JSON,
+                sqlc_core::Type::UNKNOWN,
+                sqlc_core::Type::UNKNOWN,
+                sqlc_core::Type::INET,
+                sqlc_core::Type::CIDR,
+                sqlc_core::Type::MACADDR,
+                sqlc_core::Type::POINT,
+                sqlc_core::Type::BOX,
+                sqlc_core::Type::PATH,
+                sqlc_core::Type::BIT,
+                sqlc_core::Type::VARBIT,
+                sqlc_core::Type::TIMESTAMPTZ,
+                sqlc_core::Type::TIMESTAMPTZ,
+            ],
+        )
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client
+        .query_opt(
+            &stmt,
             &[
                 &arg.name,
                 &arg.bio,
@@ -144,29 +184,45 @@ pub(crate) async fn create_author_full(
                 &arg.updated_at,
             ],
         )
-        .await?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) async fn delete_author(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
     id: i64,
 ) -> sqlc_core::Result<()> {
-    client.execute(DELETE_AUTHOR, &[&id]).await?;
+    let context = sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec");
+    let stmt = client
+        .prepare_typed(DELETE_AUTHOR, &[sqlc_core::Type::INT8])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    client.execute(&stmt, &[&id]).await.map_err(|e| context.wrap(e))?;
     Ok(())
 }
 pub(crate) async fn get_author(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
     id: i64,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(GET_AUTHOR, &[&id]).await?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(GET_AUTHOR, &[sqlc_core::Type::INT8])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&id]).await.map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) async fn list_authors(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
 ) -> sqlc_core::Result<impl std::iter::Iterator<Item = sqlc_core::Result<Author>>> {
-    let rows = client.query(LIST_AUTHORS, &[]).await?;
+    let context = sqlc_core::QueryContext::new("LIST_AUTHORS", ":many");
+    let rows = client.query(LIST_AUTHORS, &[]).await.map_err(|e| context.wrap(e))?;
     let iter = rows
         .into_iter()
-        .map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)?));
+        .map(move |row| {
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+        });
     Ok(iter)
 }
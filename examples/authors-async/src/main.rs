@@ -183,6 +183,19 @@ async fn main() -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use sqlc_core::r#async::DynDBTX;
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    // only compiled, the generated futures can be spawned and accept a `dyn DynDBTX`
+    #[allow(dead_code)]
+    fn generated_futures_are_send(client: &tokio_postgres::Client, dyn_client: &dyn DynDBTX) {
+        assert_send(&crate::db::get_author(client, 1));
+        assert_send(&crate::db::list_authors(client));
+        assert_send(&crate::db::get_author(dyn_client, 1));
+        assert_send(&crate::db::list_authors(dyn_client));
+    }
+
     #[test]
     fn test_all_queries() {
         crate::main().unwrap()
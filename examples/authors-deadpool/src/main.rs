@@ -2,7 +2,6 @@ use deadpool_postgres::{Config, Runtime};
 use geo_types::line_string;
 use itertools::Itertools;
 use postgresql_embedded::{PostgreSQL, Result};
-use std::ops::{Deref, DerefMut};
 use tokio_postgres::NoTls;
 
 #[path = "./db/gen.rs"]
@@ -37,28 +36,23 @@ async fn main() -> Result<()> {
         .expect("failed to create pool");
     let mut db_client = pool.get().await.expect("failed to get client from pool");
 
-    let client = db_client.deref_mut().deref_mut();
-
-    client
+    db_client
         .execute("CREATE extension hstore", &[])
         .await
         .unwrap();
 
     embedded::migrations::runner()
-        .run_async(client)
+        .run_async(&mut **db_client)
         .await
         .expect("failed to load migrations");
 
-    let db_client = pool.get().await.expect("failed to get client from pool");
-    let client = db_client.deref().deref();
-
-    let authors = db::list_authors(client).await.unwrap();
+    let authors = db::list_authors_pool(&pool).await.unwrap();
     assert_eq!(authors.try_len().unwrap(), 0);
 
-    let author_res_err = db::get_author(client, 1).await.is_err();
+    let author_res_err = db::get_author_pool(&pool, 1).await.is_err();
     assert_eq!(author_res_err, true);
 
-    let delete_res = db::delete_author(client, 1).await.is_ok();
+    let delete_res = db::delete_author_pool(&pool, 1).await.is_ok();
     assert_eq!(delete_res, true);
 
     let author_full_req = db::CreateAuthorFullParams {
@@ -100,7 +94,7 @@ async fn main() -> Result<()> {
         created_at: time::OffsetDateTime::now_utc(),
         updated_at: time::OffsetDateTime::now_utc(),
     };
-    let author_full_res = db::create_author_full(client, author_full_req.clone())
+    let author_full_res = db::create_author_full_pool(&pool, author_full_req.clone())
         .await
         .unwrap();
     assert_eq!(author_full_res.name, author_full_req.name);
@@ -128,22 +122,20 @@ async fn main() -> Result<()> {
     assert!(author_full_res.id == 1);
     println!("{author_full_res:#?}");
 
-    let delete_res = db::delete_author(client, 1).await.is_ok();
+    let delete_res = db::delete_author_pool(&pool, 1).await.is_ok();
     assert_eq!(delete_res, true);
 
     let author1_req = db::CreateAuthorParams {
         name: "Author 1".to_string(),
         bio: None,
     };
-    let author1_res = db::create_author(client, author1_req.clone())
-        .await
-        .unwrap();
+    let author1_res = db::create_author_pool(&pool, author1_req.clone()).await.unwrap();
     assert_eq!(author1_res.name, author1_req.name);
     assert_eq!(author1_res.bio, author1_req.bio.clone());
     assert!(author1_res.id == 2);
 
     let mut authors_list_prepared = vec![author1_res.clone()];
-    let authors: Vec<_> = db::list_authors(client)
+    let authors: Vec<_> = db::list_authors_pool(&pool)
         .await
         .unwrap()
         .try_collect()
@@ -155,16 +147,14 @@ async fn main() -> Result<()> {
         name: "Author 2".to_string(),
         bio: Some("My name is Author 2".to_string()),
     };
-    let author2_res = db::create_author(client, author2_req.clone())
-        .await
-        .unwrap();
+    let author2_res = db::create_author_pool(&pool, author2_req.clone()).await.unwrap();
     assert_eq!(author2_res.name, author2_req.name);
     assert_eq!(author2_res.bio, author2_req.bio);
     assert!(author2_res.id == 3);
 
     authors_list_prepared.push(author2_res.clone());
 
-    let authors: Vec<_> = db::list_authors(client)
+    let authors: Vec<_> = db::list_authors_pool(&pool)
         .await
         .unwrap()
         .try_collect()
@@ -172,11 +162,11 @@ async fn main() -> Result<()> {
     assert_eq!(authors.len(), 2);
     assert_eq!(authors, authors_list_prepared);
 
-    let author = db::get_author(client, 2).await.unwrap();
+    let author = db::get_author_pool(&pool, 2).await.unwrap();
     assert_eq!(author, author1_res);
 
-    db::delete_author(client, 2).await.unwrap();
-    let authors: Vec<_> = db::list_authors(client)
+    db::delete_author_pool(&pool, 2).await.unwrap();
+    let authors: Vec<_> = db::list_authors_pool(&pool)
         .await
         .unwrap()
         .try_collect()
@@ -184,6 +174,16 @@ async fn main() -> Result<()> {
     assert_eq!(authors.len(), 1);
     assert_eq!(authors, authors_list_prepared[1..]);
 
+    // the functions taking a client run in transactions of pooled connections
+    let tx = db_client.transaction().await.unwrap();
+    db::delete_author(&tx, 3).await.unwrap();
+    assert!(matches!(
+        db::get_author(&tx, 3).await.unwrap_err().inner(),
+        sqlc_core::Error::NotFound
+    ));
+    tx.rollback().await.unwrap();
+    assert_eq!(db::get_author_pool(&pool, 3).await.unwrap(), author2_res);
+
     postgresql.stop().await
 }
 
@@ -5,6 +5,12 @@ where id = $1
 limit 1
 ;
 
+-- name: GetAuthorBio :one
+select bio
+from authors
+where id = $1
+;
+
 -- name: ListAuthors :many
 select *
 from authors
@@ -41,7 +47,20 @@ INSERT INTO authors (
 )
 RETURNING *;
 
+-- name: CreateAuthorReturningId :execlastid
+INSERT INTO authors (name) VALUES ($1)
+RETURNING id;
+
+-- name: CreateAuthors :copyfrom
+INSERT INTO authors (name, bio) VALUES ($1, $2);
+
 -- name: DeleteAuthor :exec
 delete from authors
 where id = $1
 ;
+
+-- name: UpdateAuthorBio :execrows
+update authors
+set bio = $2
+where id = $1
+;
@@ -8,6 +8,10 @@ INSERT INTO authors (
 )
 RETURNING id, uuid, name, genre, bio, data, attrs, ip_inet, ip_cidr, mac_address, geo_point, geo_rect, geo_path, bit_a, varbit_a, created_at, updated_at
 "#;
+pub(crate) const CREATE_AUTHORS: &str = r#"
+INSERT INTO authors (name, bio) VALUES ($1, $2)
+"#;
+pub(crate) const CREATE_AUTHORS_COPY: &str = r#"COPY "authors" ("name", "bio") FROM STDIN (FORMAT binary)"#;
 pub(crate) const CREATE_AUTHOR_FULL: &str = r#"
 INSERT INTO authors (
   name, 
@@ -30,6 +34,10 @@ INSERT INTO authors (
 )
 RETURNING id, uuid, name, genre, bio, data, attrs, ip_inet, ip_cidr, mac_address, geo_point, geo_rect, geo_path, bit_a, varbit_a, created_at, updated_at
 "#;
+pub(crate) const CREATE_AUTHOR_RETURNING_ID: &str = r#"
+INSERT INTO authors (name) VALUES ($1)
+RETURNING id
+"#;
 pub(crate) const DELETE_AUTHOR: &str = r#"
 delete from authors
 where id = $1
@@ -40,12 +48,29 @@ from authors
 where id = $1
 limit 1
 "#;
+pub(crate) const GET_AUTHOR_BIO: &str = r#"
+select bio
+from authors
+where id = $1
+"#;
 pub(crate) const LIST_AUTHORS: &str = r#"
 select id, uuid, name, genre, bio, data, attrs, ip_inet, ip_cidr, mac_address, geo_point, geo_rect, geo_path, bit_a, varbit_a, created_at, updated_at
 from authors
 order by name
 "#;
-#[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql)]
+pub(crate) const UPDATE_AUTHOR_BIO: &str = r#"
+update authors
+set bio = $2
+where id = $1
+"#;
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    postgres_derive::ToSql,
+    postgres_derive::FromSql,
+    sqlc_core::FromPostgresRow
+)]
 #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "hash", derive(Eq, Hash))]
 #[postgres(name = "type_genre")]
@@ -112,20 +137,64 @@ pub(crate) struct CreateAuthorParams {
     pub name: String,
     pub bio: Option<String>,
 }
+#[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
+#[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "hash", derive(Eq, Hash))]
+pub(crate) struct CreateAuthorsParams {
+    pub name: String,
+    pub bio: Option<String>,
+}
+#[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
+#[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "hash", derive(Eq, Hash))]
+pub(crate) struct UpdateAuthorBioParams {
+    pub id: i64,
+    pub bio: Option<String>,
+}
 pub(crate) fn create_author(
-    client: &mut impl sqlc_core::DBTX,
+    client: &mut impl sqlc_core::sync::DBTX,
     arg: CreateAuthorParams,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(CREATE_AUTHOR, &[&arg.name, &arg.bio])?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(CREATE_AUTHOR, &[sqlc_core::Type::TEXT, sqlc_core::Type::TEXT])
+        .map_err(|e| context.wrap(e))?;
+    let row = client
+        .query_opt(&stmt, &[&arg.name, &arg.bio])
+        .map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) fn create_author_full(
-    client: &mut impl sqlc_core::DBTX,
+    client: &mut impl sqlc_core::sync::DBTX,
     arg: CreateAuthorFullParams,
 ) -> sqlc_core::Result<Author> {
-    let row = client
-        .query_one(
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR_FULL", ":one");
+    let stmt = client
+        .prepare_typed(
             CREATE_AUTHOR_FULL,
+            &[
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::JSON,
+                sqlc_core::Type::UNKNOWN,
+                sqlc_core::Type::UNKNOWN,
+                sqlc_core::Type::INET,
+                sqlc_core::Type::CIDR,
+                sqlc_core::Type::MACADDR,
+                sqlc_core::Type::POINT,
+                sqlc_core::Type::BOX,
+                sqlc_core::Type::PATH,
+                sqlc_core::Type::BIT,
+                sqlc_core::Type::VARBIT,
+                sqlc_core::Type::TIMESTAMPTZ,
+                sqlc_core::Type::TIMESTAMPTZ,
+            ],
+        )
+        .map_err(|e| context.wrap(e))?;
+    let row = client
+        .query_opt(
+            &stmt,
             &[
                 &arg.name,
                 &arg.bio,
@@ -143,29 +212,107 @@ pub(crate) fn create_author_full(
                 &arg.created_at,
                 &arg.updated_at,
             ],
-        )?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+        )
+        .map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+}
+pub(crate) fn create_author_returning_id(
+    client: &mut impl sqlc_core::sync::DBTX,
+    name: String,
+) -> sqlc_core::Result<i64> {
+    let context = sqlc_core::QueryContext::new(
+        "CREATE_AUTHOR_RETURNING_ID",
+        ":execlastid",
+    );
+    let stmt = client
+        .prepare_typed(CREATE_AUTHOR_RETURNING_ID, &[sqlc_core::Type::TEXT])
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&name]).map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    row.try_get(0).map_err(|e| context.wrap(e))
+}
+pub(crate) fn create_authors<I>(
+    client: &mut impl sqlc_core::sync::DBTX,
+    arg_list: I,
+) -> sqlc_core::Result<u64>
+where
+    I: IntoIterator,
+    I::Item: std::borrow::Borrow<CreateAuthorsParams>,
+{
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHORS", ":copyfrom");
+    let stmt = client
+        .prepare_typed(CREATE_AUTHORS, &[sqlc_core::Type::TEXT, sqlc_core::Type::TEXT])
+        .map_err(|e| context.wrap(e))?;
+    let sink = client.copy_in(CREATE_AUTHORS_COPY).map_err(|e| context.wrap(e))?;
+    let mut writer = sqlc_core::sync::BinaryCopyInWriter::new(sink, stmt.params());
+    for item in arg_list {
+        use std::borrow::Borrow;
+        let arg = item.borrow();
+        writer.write(&[&arg.name, &arg.bio]).map_err(|e| context.wrap(e))?;
+    }
+    writer.finish().map_err(|e| context.wrap(e))
 }
 pub(crate) fn delete_author(
-    client: &mut impl sqlc_core::DBTX,
+    client: &mut impl sqlc_core::sync::DBTX,
     id: i64,
 ) -> sqlc_core::Result<()> {
-    client.execute(DELETE_AUTHOR, &[&id])?;
+    let context = sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec");
+    let stmt = client
+        .prepare_typed(DELETE_AUTHOR, &[sqlc_core::Type::INT8])
+        .map_err(|e| context.wrap(e))?;
+    client.execute(&stmt, &[&id]).map_err(|e| context.wrap(e))?;
     Ok(())
 }
 pub(crate) fn get_author(
-    client: &mut impl sqlc_core::DBTX,
+    client: &mut impl sqlc_core::sync::DBTX,
     id: i64,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(GET_AUTHOR, &[&id])?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(GET_AUTHOR, &[sqlc_core::Type::INT8])
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&id]).map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+}
+pub(crate) fn get_author_bio(
+    client: &mut impl sqlc_core::sync::DBTX,
+    id: i64,
+) -> sqlc_core::Result<Option<String>> {
+    let context = sqlc_core::QueryContext::new("GET_AUTHOR_BIO", ":one");
+    let stmt = client
+        .prepare_typed(GET_AUTHOR_BIO, &[sqlc_core::Type::INT8])
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&id]).map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) fn list_authors(
-    client: &mut impl sqlc_core::DBTX,
+    client: &mut impl sqlc_core::sync::DBTX,
 ) -> sqlc_core::Result<impl std::iter::Iterator<Item = sqlc_core::Result<Author>>> {
-    let rows = client.query(LIST_AUTHORS, &[])?;
+    let context = sqlc_core::QueryContext::new("LIST_AUTHORS", ":many");
+    let rows = client.query(LIST_AUTHORS, &[]).map_err(|e| context.wrap(e))?;
     let iter = rows
         .into_iter()
-        .map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)?));
+        .map(move |row| {
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+        });
     Ok(iter)
 }
+pub(crate) fn update_author_bio(
+    client: &mut impl sqlc_core::sync::DBTX,
+    arg: UpdateAuthorBioParams,
+) -> sqlc_core::Result<u64> {
+    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR_BIO", ":execrows");
+    let stmt = client
+        .prepare_typed(
+            UPDATE_AUTHOR_BIO,
+            &[sqlc_core::Type::INT8, sqlc_core::Type::TEXT],
+        )
+        .map_err(|e| context.wrap(e))?;
+    let rows_affected = client
+        .execute(&stmt, &[&arg.id, &arg.bio])
+        .map_err(|e| context.wrap(e))?;
+    Ok(rows_affected)
+}
@@ -43,6 +43,8 @@ fn main() -> Result<()> {
 
     let author_res_err = db::get_author(&mut client, 1).is_err();
     assert_eq!(author_res_err, true);
+    let author_res_err = db::get_author(&mut client, 1).unwrap_err();
+    assert!(matches!(author_res_err.inner(), sqlc_core::Error::NotFound));
 
     let delete_res = db::delete_author(&mut client, 1).is_ok();
     assert_eq!(delete_res, true);
@@ -165,6 +167,56 @@ fn main() -> Result<()> {
     assert_eq!(authors.len(), 1);
     assert_eq!(authors, authors_list_prepared[1..]);
 
+    let update_bio_req = db::UpdateAuthorBioParams {
+        id: author2_res.id,
+        bio: Some("Updated bio of Author 2".to_string()),
+    };
+    let rows_affected = db::update_author_bio(&mut client, update_bio_req.clone()).unwrap();
+    assert_eq!(rows_affected, 1);
+
+    let author = db::get_author(&mut client, author2_res.id).unwrap();
+    assert_eq!(author.bio, update_bio_req.bio);
+
+    let bio = db::get_author_bio(&mut client, author2_res.id).unwrap();
+    assert_eq!(bio, update_bio_req.bio);
+
+    let rows_affected = db::update_author_bio(
+        &mut client,
+        db::UpdateAuthorBioParams { id: 2, bio: None },
+    )
+    .unwrap();
+    assert_eq!(rows_affected, 0);
+
+    let author_id =
+        db::create_author_returning_id(&mut client, "Author Returning Id".to_string()).unwrap();
+    let author = db::get_author(&mut client, author_id).unwrap();
+    assert_eq!(author.name, "Author Returning Id");
+    db::delete_author(&mut client, author_id).unwrap();
+
+    let tx_res = sqlc_core::sync::transaction(&mut client, |tx| {
+        db::create_author_returning_id(tx, "Author Rolled Back".to_string())?;
+        db::get_author(tx, 0)
+    });
+    assert!(tx_res.is_err());
+    let rolled_back = db::list_authors(&mut client)
+        .unwrap()
+        .map(|author| author.unwrap())
+        .any(|author| author.name == "Author Rolled Back");
+    assert!(!rolled_back);
+
+    let copied_authors = vec![
+        db::CreateAuthorsParams {
+            name: "Author Copy 1".to_string(),
+            bio: None,
+        },
+        db::CreateAuthorsParams {
+            name: "Author Copy 2".to_string(),
+            bio: Some("Copied in bulk".to_string()),
+        },
+    ];
+    let rows_copied = db::create_authors(&mut client, &copied_authors).unwrap();
+    assert_eq!(rows_copied, 2);
+
     let mut transaction = client.transaction().expect("could not create transaction");
 
     let create_authors_params = vec![
@@ -64,7 +64,14 @@ UPDATE books
 SET title = $1, tags = $2
 WHERE book_id = $3
 "#;
-#[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql)]
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    postgres_derive::ToSql,
+    postgres_derive::FromSql,
+    sqlc_core::FromPostgresRow
+)]
 #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "hash", derive(Eq, Hash))]
 #[postgres(name = "book_type")]
@@ -118,12 +125,29 @@ pub(crate) struct UpdateBookParams {
     pub book_id: i32,
 }
 pub(crate) async fn all_books(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
 ) -> sqlc_core::Result<impl std::iter::Iterator<Item = sqlc_core::Result<Book>>> {
-    let rows = client.query(ALL_BOOKS, &[]).await?;
+    let context = sqlc_core::QueryContext::new("ALL_BOOKS", ":many");
+    let rows = client.query(ALL_BOOKS, &[]).await.map_err(|e| context.wrap(e))?;
     let iter = rows
         .into_iter()
-        .map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)?));
+        .map(move |row| {
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+        });
+    Ok(iter)
+}
+pub(crate) async fn all_books_pool(
+    pool: &deadpool_postgres::Pool,
+) -> sqlc_core::Result<impl std::iter::Iterator<Item = sqlc_core::Result<Book>>> {
+    let context = sqlc_core::QueryContext::new("ALL_BOOKS", ":many");
+    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+    let stmt = client.prepare_cached(ALL_BOOKS).await.map_err(|e| context.wrap(e))?;
+    let rows = client.query(&stmt, &[]).await.map_err(|e| context.wrap(e))?;
+    let iter = rows
+        .into_iter()
+        .map(move |row| {
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+        });
     Ok(iter)
 }
 pub(crate) async fn books_by_year<'a, C, I>(
@@ -139,31 +163,58 @@ pub(crate) async fn books_by_year<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<i32> + 'a,
 {
-    let stmt = client.prepare(BOOKS_BY_YEAR).await?;
+    let context = sqlc_core::QueryContext::new("BOOKS_BY_YEAR", ":batchmany");
+    let stmt = client
+        .prepare_typed(BOOKS_BY_YEAR, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let year = item.borrow();
-            let rows = client.query(&stmt, &[&year]).await?;
+            let rows = client.query(&stmt, &[&year]).await.map_err(|e| context.wrap(e))?;
             let result = rows
                 .into_iter()
-                .map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)));
+                .map(move |row| Ok(
+                    sqlc_core::FromPostgresRow::from_row(&row)
+                        .map_err(|e| context.wrap(e)),
+                ));
             Ok(Box::pin(futures::stream::iter(result)))
         })
     };
     Ok(futures::stream::iter(year_list.into_iter().map(fut)))
 }
 pub(crate) async fn create_author(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+    name: String,
+) -> sqlc_core::Result<Author> {
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(CREATE_AUTHOR, &[sqlc_core::Type::TEXT])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&name]).await.map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+}
+pub(crate) async fn create_author_pool(
+    pool: &deadpool_postgres::Pool,
     name: String,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(CREATE_AUTHOR, &[&name]).await?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("CREATE_AUTHOR", ":one");
+    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+    let stmt = client
+        .prepare_typed_cached(CREATE_AUTHOR, &[sqlc_core::Type::TEXT])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&name]).await.map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) async fn create_book<'a, C, I>(
     client: &'a C,
@@ -174,18 +225,33 @@ pub(crate) async fn create_book<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<CreateBookParams> + 'a,
 {
-    let stmt = client.prepare(CREATE_BOOK).await?;
+    let context = sqlc_core::QueryContext::new("CREATE_BOOK", ":batchone");
+    let stmt = client
+        .prepare_typed(
+            CREATE_BOOK,
+            &[
+                sqlc_core::Type::INT4,
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::UNKNOWN,
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::INT4,
+                sqlc_core::Type::TIMESTAMPTZ,
+                sqlc_core::Type::VARCHAR_ARRAY,
+            ],
+        )
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let arg = item.borrow();
             let row = client
-                .query_one(
+                .query_opt(
                     &stmt,
                     &[
                         &arg.author_id,
@@ -197,8 +263,10 @@ where
                         &arg.tags,
                     ],
                 )
-                .await?;
-            Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+                .await
+                .map_err(|e| context.wrap(e))?
+                .ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
         })
     };
     Ok(futures::stream::iter(arg_list.into_iter().map(fut)))
@@ -212,28 +280,56 @@ pub(crate) async fn delete_book<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<i32> + 'a,
 {
-    let stmt = client.prepare(DELETE_BOOK).await?;
+    let context = sqlc_core::QueryContext::new("DELETE_BOOK", ":batchexec");
+    let stmt = client
+        .prepare_typed(DELETE_BOOK, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let book_id = item.borrow();
-            client.execute(&stmt, &[&book_id]).await?;
+            client.execute(&stmt, &[&book_id]).await.map_err(|e| context.wrap(e))?;
             Ok(())
         })
     };
     Ok(futures::stream::iter(book_id_list.into_iter().map(fut)))
 }
 pub(crate) async fn delete_book_exec_result(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+    book_id: i32,
+) -> sqlc_core::Result<sqlc_core::ExecResult> {
+    let context = sqlc_core::QueryContext::new("DELETE_BOOK_EXEC_RESULT", ":execresult");
+    let stmt = client
+        .prepare_typed(DELETE_BOOK_EXEC_RESULT, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let rows_affected = client
+        .execute(&stmt, &[&book_id])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    Ok(sqlc_core::ExecResult::new("DELETE", rows_affected))
+}
+pub(crate) async fn delete_book_exec_result_pool(
+    pool: &deadpool_postgres::Pool,
     book_id: i32,
-) -> sqlc_core::Result<()> {
-    client.execute(DELETE_BOOK_EXEC_RESULT, &[&book_id]).await?;
-    Ok(())
+) -> sqlc_core::Result<sqlc_core::ExecResult> {
+    let context = sqlc_core::QueryContext::new("DELETE_BOOK_EXEC_RESULT", ":execresult");
+    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+    let stmt = client
+        .prepare_typed_cached(DELETE_BOOK_EXEC_RESULT, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let rows_affected = client
+        .execute(&stmt, &[&book_id])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    Ok(sqlc_core::ExecResult::new("DELETE", rows_affected))
 }
 pub(crate) async fn delete_book_named_func<'a, C, I>(
     client: &'a C,
@@ -244,17 +340,21 @@ pub(crate) async fn delete_book_named_func<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<i32> + 'a,
 {
-    let stmt = client.prepare(DELETE_BOOK_NAMED_FUNC).await?;
+    let context = sqlc_core::QueryContext::new("DELETE_BOOK_NAMED_FUNC", ":batchexec");
+    let stmt = client
+        .prepare_typed(DELETE_BOOK_NAMED_FUNC, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let book_id = item.borrow();
-            client.execute(&stmt, &[&book_id]).await?;
+            client.execute(&stmt, &[&book_id]).await.map_err(|e| context.wrap(e))?;
             Ok(())
         })
     };
@@ -269,28 +369,52 @@ pub(crate) async fn delete_book_named_sign<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<i32> + 'a,
 {
-    let stmt = client.prepare(DELETE_BOOK_NAMED_SIGN).await?;
+    let context = sqlc_core::QueryContext::new("DELETE_BOOK_NAMED_SIGN", ":batchexec");
+    let stmt = client
+        .prepare_typed(DELETE_BOOK_NAMED_SIGN, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let book_id = item.borrow();
-            client.execute(&stmt, &[&book_id]).await?;
+            client.execute(&stmt, &[&book_id]).await.map_err(|e| context.wrap(e))?;
             Ok(())
         })
     };
     Ok(futures::stream::iter(book_id_list.into_iter().map(fut)))
 }
 pub(crate) async fn get_author(
-    client: &impl sqlc_core::DBTX,
+    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
     author_id: i32,
 ) -> sqlc_core::Result<Author> {
-    let row = client.query_one(GET_AUTHOR, &[&author_id]).await?;
-    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+    let stmt = client
+        .prepare_typed(GET_AUTHOR, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&author_id]).await.map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+}
+pub(crate) async fn get_author_pool(
+    pool: &deadpool_postgres::Pool,
+    author_id: i32,
+) -> sqlc_core::Result<Author> {
+    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+    let stmt = client
+        .prepare_typed_cached(GET_AUTHOR, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
+    let row = client.query_opt(&stmt, &[&author_id]).await.map_err(|e| context.wrap(e))?;
+    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
 }
 pub(crate) async fn get_biography<'a, C, I>(
     client: &'a C,
@@ -301,18 +425,26 @@ pub(crate) async fn get_biography<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<i32> + 'a,
 {
-    let stmt = client.prepare(GET_BIOGRAPHY).await?;
+    let context = sqlc_core::QueryContext::new("GET_BIOGRAPHY", ":batchone");
+    let stmt = client
+        .prepare_typed(GET_BIOGRAPHY, &[sqlc_core::Type::INT4])
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let author_id = item.borrow();
-            let row = client.query_one(&stmt, &[&author_id]).await?;
-            Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+            let row = client
+                .query_opt(&stmt, &[&author_id])
+                .await
+                .map_err(|e| context.wrap(e))?
+                .ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
         })
     };
     Ok(futures::stream::iter(author_id_list.into_iter().map(fut)))
@@ -326,17 +458,31 @@ pub(crate) async fn update_book<'a, C, I>(
     > + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX + ?Sized,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<UpdateBookParams> + 'a,
 {
-    let stmt = client.prepare(UPDATE_BOOK).await?;
+    let context = sqlc_core::QueryContext::new("UPDATE_BOOK", ":batchexec");
+    let stmt = client
+        .prepare_typed(
+            UPDATE_BOOK,
+            &[
+                sqlc_core::Type::TEXT,
+                sqlc_core::Type::VARCHAR_ARRAY,
+                sqlc_core::Type::INT4,
+            ],
+        )
+        .await
+        .map_err(|e| context.wrap(e))?;
     let fut = move |item: <I as IntoIterator>::Item| {
         let stmt = stmt.clone();
         Box::pin(async move {
             use std::borrow::Borrow;
             let arg = item.borrow();
-            client.execute(&stmt, &[&arg.title, &arg.tags, &arg.book_id]).await?;
+            client
+                .execute(&stmt, &[&arg.title, &arg.tags, &arg.book_id])
+                .await
+                .map_err(|e| context.wrap(e))?;
             Ok(())
         })
     };
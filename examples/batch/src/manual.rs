@@ -111,7 +111,7 @@ pub(crate) struct UpdateBookParams {
     pub book_id: i32,
 }
 pub(crate) async fn all_books(
-    client: &impl sqlc_core::DBTX,
+    client: &impl sqlc_core::r#async::DBTX,
 ) -> sqlc_core::Result<impl std::iter::Iterator<Item = sqlc_core::Result<Book>>> {
     let rows = client.query(ALL_BOOKS, &[]).await?;
     let iter = rows
@@ -120,14 +120,14 @@ pub(crate) async fn all_books(
     Ok(iter)
 }
 pub(crate) async fn create_author(
-    client: &impl sqlc_core::DBTX,
+    client: &impl sqlc_core::r#async::DBTX,
     name: &str,
 ) -> sqlc_core::Result<Author> {
     let row = client.query_one(CREATE_AUTHOR, &[&name]).await?;
     Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
 }
 
-pub(crate) async fn create_book<'a, T: sqlc_core::DBTX>(
+pub(crate) async fn create_book<'a, T: sqlc_core::r#async::DBTX>(
     client: &'a T,
     arg_list: &'a [CreateBookParams],
 ) -> sqlc_core::Result<
@@ -164,7 +164,7 @@ pub(crate) async fn update_book<'a, C, I>(
     impl futures::Stream<Item = impl futures::Future<Output = sqlc_core::Result<()>> + 'a> + 'a,
 >
 where
-    C: sqlc_core::DBTX,
+    C: sqlc_core::r#async::DBTX,
     I: IntoIterator + 'a,
     I::Item: std::borrow::Borrow<UpdateBookParams> + 'a,
 {
@@ -183,7 +183,7 @@ where
     Ok(futures::stream::iter(arg_list.into_iter().map(fut)))
 }
 
-pub(crate) async fn books_by_year<'a, T: sqlc_core::DBTX>(
+pub(crate) async fn books_by_year<'a, T: sqlc_core::r#async::DBTX>(
     client: &'a T,
     year_list: impl std::iter::Iterator<Item = i32> + 'a,
 ) -> sqlc_core::Result<
@@ -0,0 +1,106 @@
+use super::tokio_pg::{AsStatement, StatementRef, DBTX};
+use bytes::Bytes;
+use deadpool_postgres::{ClientWrapper, Object, Transaction};
+use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::{CopyInSink, Error, Row, RowStream, Statement};
+
+/// Statement for `$statement`, query strings being prepared with the statement cache of
+/// the connection of `$client`
+macro_rules! cached_statement {
+    ($client:expr, $statement:expr) => {
+        match $statement.as_statement() {
+            StatementRef::Statement(statement) => statement.clone(),
+            StatementRef::Query(query) => $client.prepare_cached(query).await?,
+        }
+    };
+}
+
+/// Implements `DBTX` for a deadpool client or transaction, preparing statements and query
+/// strings with the statement cache of its connection and running them on the wrapped
+/// `$inner`
+macro_rules! impl_dbtx {
+    ($t:ty, $inner:ty) => {
+        impl DBTX for $t {
+            async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+                self.prepare_cached(query).await
+            }
+
+            async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+                self.prepare_typed_cached(query, types).await
+            }
+
+            async fn execute<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<u64, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                <$inner>::execute(self, &statement, params).await
+            }
+
+            async fn query_one<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Row, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                <$inner>::query_one(self, &statement, params).await
+            }
+
+            async fn query_opt<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Option<Row>, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                <$inner>::query_opt(self, &statement, params).await
+            }
+
+            async fn query<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<Vec<Row>, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                <$inner>::query(self, &statement, params).await
+            }
+
+            async fn query_raw<T>(
+                &self,
+                statement: &T,
+                params: &[&(dyn ToSql + Sync)],
+            ) -> Result<RowStream, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                let params = params.iter().map(|p| *p as &dyn ToSql);
+                <$inner>::query_raw(self, &statement, params).await
+            }
+
+            async fn copy_in<T>(&self, statement: &T) -> Result<CopyInSink<Bytes>, Error>
+            where
+                T: ?Sized + AsStatement,
+            {
+                let statement = cached_statement!(self, statement);
+                <$inner>::copy_in(self, &statement).await
+            }
+        }
+    };
+}
+
+impl_dbtx!(ClientWrapper, tokio_postgres::Client);
+impl_dbtx!(Object, tokio_postgres::Client);
+impl_dbtx!(Transaction<'_>, tokio_postgres::Transaction<'_>);
@@ -3,3 +3,6 @@ pub mod pg;
 
 #[cfg(feature = "with-tokio-postgres")]
 pub mod tokio_pg;
+
+#[cfg(feature = "with-deadpool")]
+mod deadpool_pg;
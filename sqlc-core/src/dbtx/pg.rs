@@ -1,12 +1,35 @@
-use postgres::types::ToSql;
-use postgres::{Client, Error, Row, Statement, ToStatement, Transaction};
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::{ToSql, Type};
+use postgres::{Client, CopyInWriter, Error, Row, ToStatement, Transaction};
+
+pub use postgres::binary_copy::BinaryCopyInWriter;
+pub use postgres::Statement;
+
+/// Iterator over the rows of a query, fetching them from the server lazily
+pub struct RowIter<'a>(postgres::RowIter<'a>);
+
+impl Iterator for RowIter<'_> {
+    type Item = Result<Row, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().transpose()
+    }
+}
 
 pub trait DBTX {
     fn prepare(&mut self, query: &str) -> Result<Statement, Error>;
+    fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error>;
     fn execute<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
         T: ?Sized + ToStatement + Sync + Send;
     fn query_one<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Row, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send;
+    fn query_opt<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
     where
         T: ?Sized + ToStatement + Sync + Send;
     fn query<T>(
@@ -16,6 +39,16 @@ pub trait DBTX {
     ) -> Result<Vec<Row>, Error>
     where
         T: ?Sized + ToStatement + Sync + Send;
+    fn query_raw<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowIter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send;
+    fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send;
 }
 
 impl DBTX for Transaction<'_> {
@@ -23,6 +56,10 @@ impl DBTX for Transaction<'_> {
         Transaction::prepare(self, query)
     }
 
+    fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        Transaction::prepare_typed(self, query, types)
+    }
+
     fn execute<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
@@ -37,12 +74,42 @@ impl DBTX for Transaction<'_> {
         Transaction::query_one(self, statement, params)
     }
 
+    fn query_opt<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Transaction::query_opt(self, statement, params)
+    }
+
     fn query<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
     {
         Transaction::query(self, statement, params)
     }
+
+    fn query_raw<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowIter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        let params = params.iter().map(|p| *p as &dyn ToSql);
+        Ok(RowIter(Transaction::query_raw(self, statement, params)?))
+    }
+
+    fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Transaction::copy_in(self, query)
+    }
 }
 
 impl DBTX for Client {
@@ -50,6 +117,10 @@ impl DBTX for Client {
         Client::prepare(self, query)
     }
 
+    fn prepare_typed(&mut self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        Client::prepare_typed(self, query, types)
+    }
+
     fn execute<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
@@ -64,10 +135,40 @@ impl DBTX for Client {
         Client::query_one(self, statement, params)
     }
 
+    fn query_opt<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::query_opt(self, statement, params)
+    }
+
     fn query<T>(&mut self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>, Error>
     where
         T: ?Sized + ToStatement + Sync + Send,
     {
         Client::query(self, statement, params)
     }
+
+    fn query_raw<T>(
+        &mut self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowIter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        let params = params.iter().map(|p| *p as &dyn ToSql);
+        Ok(RowIter(Client::query_raw(self, statement, params)?))
+    }
+
+    fn copy_in<T>(&mut self, query: &T) -> Result<CopyInWriter<'_>, Error>
+    where
+        T: ?Sized + ToStatement + Sync + Send,
+    {
+        Client::copy_in(self, query)
+    }
 }
@@ -1,38 +1,107 @@
 use async_trait::async_trait;
-use tokio_postgres::types::ToSql;
-use tokio_postgres::{Client, Error, Row, Statement, ToStatement, Transaction};
+use bytes::Bytes;
+use std::future::Future;
+use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::{Client, CopyInSink, Error, Row, RowStream, ToStatement, Transaction};
 
-#[async_trait]
+pub use tokio_postgres::binary_copy::BinaryCopyInWriter;
+pub use tokio_postgres::Statement;
+
+/// Prepared statement or query string run by a [`DBTX`]
+pub trait AsStatement: ToStatement + Sync + Send {
+    /// The statement, or the query to prepare into one
+    fn as_statement(&self) -> StatementRef<'_>;
+}
+
+pub enum StatementRef<'a> {
+    Statement(&'a Statement),
+    Query(&'a str),
+}
+
+impl AsStatement for Statement {
+    fn as_statement(&self) -> StatementRef<'_> {
+        StatementRef::Statement(self)
+    }
+}
+
+impl AsStatement for str {
+    fn as_statement(&self) -> StatementRef<'_> {
+        StatementRef::Query(self)
+    }
+}
+
+impl AsStatement for String {
+    fn as_statement(&self) -> StatementRef<'_> {
+        StatementRef::Query(self)
+    }
+}
+
+/// Client or transaction the generated queries run on
+///
+/// The returned futures are not boxed. Methods being generic, the trait cannot be used
+/// as `dyn DBTX`, see [`DynDBTX`] for that.
 pub trait DBTX: Send + Sync {
-    async fn prepare(&self, query: &str) -> Result<Statement, Error>;
-    async fn execute<T>(&self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
+    fn prepare(&self, query: &str) -> impl Future<Output = Result<Statement, Error>> + Send;
+    fn prepare_typed(
+        &self,
+        query: &str,
+        types: &[Type],
+    ) -> impl Future<Output = Result<Statement, Error>> + Send;
+    fn execute<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<u64, Error>> + Send
     where
-        T: ?Sized + ToStatement + Sync + Send;
-    async fn query_one<T>(
+        T: ?Sized + AsStatement;
+    fn query_one<T>(
         &self,
         statement: &T,
         params: &[&(dyn ToSql + Sync)],
-    ) -> Result<Row, Error>
+    ) -> impl Future<Output = Result<Row, Error>> + Send
     where
-        T: ?Sized + ToStatement + Sync + Send;
-    async fn query<T>(
+        T: ?Sized + AsStatement;
+    fn query_opt<T>(
         &self,
         statement: &T,
         params: &[&(dyn ToSql + Sync)],
-    ) -> Result<Vec<Row>, Error>
+    ) -> impl Future<Output = Result<Option<Row>, Error>> + Send
+    where
+        T: ?Sized + AsStatement;
+    fn query<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<Vec<Row>, Error>> + Send
+    where
+        T: ?Sized + AsStatement;
+    fn query_raw<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> impl Future<Output = Result<RowStream, Error>> + Send
     where
-        T: ?Sized + ToStatement + Sync + Send;
+        T: ?Sized + AsStatement;
+    fn copy_in<T>(
+        &self,
+        statement: &T,
+    ) -> impl Future<Output = Result<CopyInSink<Bytes>, Error>> + Send
+    where
+        T: ?Sized + AsStatement;
 }
 
-#[async_trait]
 impl DBTX for Transaction<'_> {
     async fn prepare(&self, query: &str) -> Result<Statement, Error> {
         Transaction::prepare(self, query).await
     }
 
+    async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        Transaction::prepare_typed(self, query, types).await
+    }
+
     async fn execute<T>(&self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Transaction::execute(self, statement, params).await
     }
@@ -43,32 +112,65 @@ impl DBTX for Transaction<'_> {
         params: &[&(dyn ToSql + Sync)],
     ) -> Result<Row, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Transaction::query_one(self, statement, params).await
     }
 
+    async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        Transaction::query_opt(self, statement, params).await
+    }
+
     async fn query<T>(
         &self,
         statement: &T,
         params: &[&(dyn ToSql + Sync)],
     ) -> Result<Vec<Row>, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Transaction::query(self, statement, params).await
     }
+
+    async fn query_raw<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let params = params.iter().map(|p| *p as &dyn ToSql);
+        Transaction::query_raw(self, statement, params).await
+    }
+
+    async fn copy_in<T>(&self, statement: &T) -> Result<CopyInSink<Bytes>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        Transaction::copy_in(self, statement).await
+    }
 }
 
-#[async_trait]
 impl DBTX for Client {
     async fn prepare(&self, query: &str) -> Result<Statement, Error> {
         Client::prepare(self, query).await
     }
 
+    async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        Client::prepare_typed(self, query, types).await
+    }
+
     async fn execute<T>(&self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Client::execute(self, statement, params).await
     }
@@ -79,19 +181,227 @@ impl DBTX for Client {
         params: &[&(dyn ToSql + Sync)],
     ) -> Result<Row, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Client::query_one(self, statement, params).await
     }
 
+    async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        Client::query_opt(self, statement, params).await
+    }
+
     async fn query<T>(
         &self,
         statement: &T,
         params: &[&(dyn ToSql + Sync)],
     ) -> Result<Vec<Row>, Error>
     where
-        T: ?Sized + ToStatement + Sync + Send,
+        T: ?Sized + AsStatement,
     {
         Client::query(self, statement, params).await
     }
+
+    async fn query_raw<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let params = params.iter().map(|p| *p as &dyn ToSql);
+        Client::query_raw(self, statement, params).await
+    }
+
+    async fn copy_in<T>(&self, statement: &T) -> Result<CopyInSink<Bytes>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        Client::copy_in(self, statement).await
+    }
+}
+
+/// Object safe counterpart of [`DBTX`], implemented for every [`DBTX`]
+///
+/// Futures are boxed and statements are prepared ones, so that it can be used as
+/// `dyn DynDBTX`. `dyn DynDBTX` implements [`DBTX`] in turn, preparing query strings
+/// before running them, so that it can be passed to the generated functions.
+#[async_trait]
+pub trait DynDBTX: Send + Sync {
+    async fn prepare(&self, query: &str) -> Result<Statement, Error>;
+    async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error>;
+    async fn execute(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error>;
+    async fn query_one(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error>;
+    async fn query_opt(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>;
+    async fn query(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error>;
+    async fn query_raw(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error>;
+    async fn copy_in(&self, statement: &Statement) -> Result<CopyInSink<Bytes>, Error>;
+}
+
+#[async_trait]
+impl<C: DBTX> DynDBTX for C {
+    async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+        DBTX::prepare(self, query).await
+    }
+
+    async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        DBTX::prepare_typed(self, query, types).await
+    }
+
+    async fn execute(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<u64, Error> {
+        DBTX::execute(self, statement, params).await
+    }
+
+    async fn query_one(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error> {
+        DBTX::query_one(self, statement, params).await
+    }
+
+    async fn query_opt(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error> {
+        DBTX::query_opt(self, statement, params).await
+    }
+
+    async fn query(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error> {
+        DBTX::query(self, statement, params).await
+    }
+
+    async fn query_raw(
+        &self,
+        statement: &Statement,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error> {
+        DBTX::query_raw(self, statement, params).await
+    }
+
+    async fn copy_in(&self, statement: &Statement) -> Result<CopyInSink<Bytes>, Error> {
+        DBTX::copy_in(self, statement).await
+    }
+}
+
+impl dyn DynDBTX + '_ {
+    async fn to_statement<T>(&self, statement: &T) -> Result<Statement, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        match statement.as_statement() {
+            StatementRef::Statement(statement) => Ok(statement.clone()),
+            StatementRef::Query(query) => DynDBTX::prepare(self, query).await,
+        }
+    }
+}
+
+impl DBTX for dyn DynDBTX + '_ {
+    async fn prepare(&self, query: &str) -> Result<Statement, Error> {
+        DynDBTX::prepare(self, query).await
+    }
+
+    async fn prepare_typed(&self, query: &str, types: &[Type]) -> Result<Statement, Error> {
+        DynDBTX::prepare_typed(self, query, types).await
+    }
+
+    async fn execute<T>(&self, statement: &T, params: &[&(dyn ToSql + Sync)]) -> Result<u64, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::execute(self, &statement, params).await
+    }
+
+    async fn query_one<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Row, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::query_one(self, &statement, params).await
+    }
+
+    async fn query_opt<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::query_opt(self, &statement, params).await
+    }
+
+    async fn query<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Vec<Row>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::query(self, &statement, params).await
+    }
+
+    async fn query_raw<T>(
+        &self,
+        statement: &T,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<RowStream, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::query_raw(self, &statement, params).await
+    }
+
+    async fn copy_in<T>(&self, statement: &T) -> Result<CopyInSink<Bytes>, Error>
+    where
+        T: ?Sized + AsStatement,
+    {
+        let statement = self.to_statement(statement).await?;
+        DynDBTX::copy_in(self, &statement).await
+    }
 }
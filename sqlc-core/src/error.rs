@@ -1,16 +1,346 @@
+use crate::driver::error::SqlState;
+
+/// Error of both drivers, `postgres::Error` being the error type of `tokio-postgres`
+type DriverError = crate::driver::Error;
+
+/// Error of the generated functions and of the helpers they use
+///
+/// Generated functions wrap their errors in `Error::Query`, with the query that failed.
+/// `inner()` returns the wrapped error and `kind()` its kind, e.g.
+/// `matches!(error.inner(), Error::UniqueViolation { .. })` or
+/// `error.kind() == ErrorKind::UniqueViolation` hold for a wrapped unique violation.
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[cfg(feature = "with-deadpool")]
     #[error("deadpool-postgres error: {0}")]
-    DeadpoolError(#[from] deadpool_postgres::PoolError),
+    DeadpoolError(deadpool_postgres::PoolError),
 
-    #[cfg(feature = "with-postgres")]
+    /// Driver error without a more specific variant
     #[error("postgres error: {0}")]
-    PostgresError(#[from] postgres::Error),
+    PostgresError(DriverError),
+
+    /// A query expected to return a row returned none
+    #[error("query returned no rows")]
+    NotFound,
+
+    #[error("unique violation{}", constraint_suffix(.constraint))]
+    UniqueViolation {
+        constraint: Option<String>,
+        source: DriverError,
+    },
+
+    #[error("foreign key violation{}", constraint_suffix(.constraint))]
+    ForeignKeyViolation {
+        constraint: Option<String>,
+        source: DriverError,
+    },
+
+    #[error("check violation{}", constraint_suffix(.constraint))]
+    CheckViolation {
+        constraint: Option<String>,
+        source: DriverError,
+    },
+
+    /// Serialization failure or deadlock, the transaction can succeed when run again
+    #[error("serialization failure")]
+    SerializationFailure { source: DriverError },
+
+    /// Column that could not be decoded, with the struct field it was decoded into
+    #[error("could not decode column `{column}`{}", field_suffix(.struct_name, .field))]
+    Decode {
+        column: String,
+        /// Name of the struct, without its module path
+        struct_name: Option<String>,
+        /// Name of the field, its index for tuple structs
+        field: Option<String>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The connection was closed or failed, or the server is shutting down
+    #[error("connection lost")]
+    ConnectionLost { source: DriverError },
+
+    /// Error of a generated query function, with the query it ran
+    #[error("query `{query}` ({command}) failed")]
+    Query {
+        query: &'static str,
+        command: &'static str,
+        source: Box<Error>,
+    },
+}
 
-    #[cfg(feature = "with-tokio-postgres")]
-    #[error("tokio-postgres error: {0}")]
-    TokioPostgresError(#[from] tokio_postgres::Error),
+/// Variant of an `Error`, without its fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    NotFound,
+    UniqueViolation,
+    ForeignKeyViolation,
+    CheckViolation,
+    SerializationFailure,
+    Decode,
+    ConnectionLost,
+    Other,
+}
+
+impl ErrorKind {
+    /// Kind of a server error with the SQLSTATE `code`
+    pub fn from_sql_state(code: &SqlState) -> Self {
+        if *code == SqlState::UNIQUE_VIOLATION {
+            Self::UniqueViolation
+        } else if *code == SqlState::FOREIGN_KEY_VIOLATION {
+            Self::ForeignKeyViolation
+        } else if *code == SqlState::CHECK_VIOLATION {
+            Self::CheckViolation
+        } else if *code == SqlState::T_R_SERIALIZATION_FAILURE
+            || *code == SqlState::T_R_DEADLOCK_DETECTED
+        {
+            Self::SerializationFailure
+        } else if code.code().starts_with("08")
+            || *code == SqlState::ADMIN_SHUTDOWN
+            || *code == SqlState::CRASH_SHUTDOWN
+            || *code == SqlState::CANNOT_CONNECT_NOW
+        {
+            Self::ConnectionLost
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// Query run by a generated function, attached to the errors it returns
+#[derive(Clone, Copy, Debug)]
+pub struct QueryContext {
+    query: &'static str,
+    command: &'static str,
+}
+
+impl QueryContext {
+    /// Context of the query named by its constant, e.g. `GET_AUTHOR` and `:one`
+    pub const fn new(query: &'static str, command: &'static str) -> Self {
+        Self { query, command }
+    }
+
+    pub fn wrap(self, error: impl Into<Error>) -> Error {
+        Error::Query {
+            query: self.query,
+            command: self.command,
+            source: Box::new(error.into()),
+        }
+    }
+}
+
+impl Error {
+    pub fn decode(
+        column: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Decode {
+            column: column.into(),
+            struct_name: None,
+            field: None,
+            source: source.into(),
+        }
+    }
+
+    /// Decode error of a column read into the field `field` of `struct_name`
+    pub fn decode_field(
+        column: impl Into<String>,
+        struct_name: impl Into<String>,
+        field: impl Into<String>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self::Decode {
+            column: column.into(),
+            struct_name: Some(struct_name.into()),
+            field: Some(field.into()),
+            source: source.into(),
+        }
+    }
+
+    /// The error without the query context added by generated functions
+    pub fn inner(&self) -> &Self {
+        match self {
+            Self::Query { source, .. } => source.inner(),
+            error => error,
+        }
+    }
+
+    /// Kind of the error, the same for an error and the one it wraps with a query context
+    pub fn kind(&self) -> ErrorKind {
+        match self.inner() {
+            Self::NotFound => ErrorKind::NotFound,
+            Self::UniqueViolation { .. } => ErrorKind::UniqueViolation,
+            Self::ForeignKeyViolation { .. } => ErrorKind::ForeignKeyViolation,
+            Self::CheckViolation { .. } => ErrorKind::CheckViolation,
+            Self::SerializationFailure { .. } => ErrorKind::SerializationFailure,
+            Self::Decode { .. } => ErrorKind::Decode,
+            Self::ConnectionLost { .. } => ErrorKind::ConnectionLost,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Driver error, classified by its SQLSTATE code when it comes from the server
+    fn from_driver(error: DriverError) -> Self {
+        if error.is_closed() || is_io_error(&error) {
+            return Self::ConnectionLost { source: error };
+        }
+
+        let Some(db_error) = error.as_db_error() else {
+            return Self::PostgresError(error);
+        };
+        let constraint = db_error.constraint().map(str::to_string);
+        match ErrorKind::from_sql_state(db_error.code()) {
+            ErrorKind::UniqueViolation => Self::UniqueViolation {
+                constraint,
+                source: error,
+            },
+            ErrorKind::ForeignKeyViolation => Self::ForeignKeyViolation {
+                constraint,
+                source: error,
+            },
+            ErrorKind::CheckViolation => Self::CheckViolation {
+                constraint,
+                source: error,
+            },
+            ErrorKind::SerializationFailure => Self::SerializationFailure { source: error },
+            ErrorKind::ConnectionLost => Self::ConnectionLost { source: error },
+            _ => Self::PostgresError(error),
+        }
+    }
+}
+
+impl From<DriverError> for Error {
+    fn from(error: DriverError) -> Self {
+        Self::from_driver(error)
+    }
+}
+
+#[cfg(feature = "with-deadpool")]
+impl From<deadpool_postgres::PoolError> for Error {
+    fn from(error: deadpool_postgres::PoolError) -> Self {
+        match error {
+            deadpool_postgres::PoolError::Backend(error) => Self::from_driver(error),
+            error => Self::DeadpoolError(error),
+        }
+    }
+}
+
+fn constraint_suffix(constraint: &Option<String>) -> String {
+    constraint
+        .as_ref()
+        .map(|constraint| format!(" on `{constraint}`"))
+        .unwrap_or_default()
+}
+
+fn field_suffix(struct_name: &Option<String>, field: &Option<String>) -> String {
+    match (struct_name, field) {
+        (Some(struct_name), Some(field)) => format!(" into `{struct_name}::{field}`"),
+        (None, Some(field)) => format!(" into `{field}`"),
+        _ => String::new(),
+    }
+}
+
+fn is_io_error(error: &DriverError) -> bool {
+    std::error::Error::source(error).is_some_and(|source| source.is::<std::io::Error>())
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_kind_from_sql_state() {
+        let cases = [
+            ("23505", ErrorKind::UniqueViolation),
+            ("23503", ErrorKind::ForeignKeyViolation),
+            ("23514", ErrorKind::CheckViolation),
+            ("40001", ErrorKind::SerializationFailure),
+            ("40P01", ErrorKind::SerializationFailure),
+            ("08000", ErrorKind::ConnectionLost),
+            ("08003", ErrorKind::ConnectionLost),
+            ("08006", ErrorKind::ConnectionLost),
+            ("08P01", ErrorKind::ConnectionLost),
+            ("57P01", ErrorKind::ConnectionLost),
+            ("57P02", ErrorKind::ConnectionLost),
+            ("57P03", ErrorKind::ConnectionLost),
+            ("23502", ErrorKind::Other),
+            ("42P01", ErrorKind::Other),
+            ("57014", ErrorKind::Other),
+        ];
+        for (code, kind) in cases {
+            assert_eq!(
+                ErrorKind::from_sql_state(&SqlState::from_code(code)),
+                kind,
+                "{code}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_context_wrap() {
+        let error = QueryContext::new("GET_AUTHOR", ":one").wrap(Error::NotFound);
+        assert_eq!(error.to_string(), "query `GET_AUTHOR` (:one) failed");
+        assert!(matches!(
+            error,
+            Error::Query {
+                query: "GET_AUTHOR",
+                command: ":one",
+                ..
+            }
+        ));
+        assert_eq!(
+            error.source().unwrap().to_string(),
+            "query returned no rows"
+        );
+    }
+
+    #[test]
+    fn test_inner() {
+        let context = QueryContext::new("GET_AUTHOR", ":one");
+        let error = context.wrap(context.wrap(Error::NotFound));
+        assert!(matches!(error.inner(), Error::NotFound));
+        assert!(matches!(Error::NotFound.inner(), Error::NotFound));
+    }
+
+    #[test]
+    fn test_kind() {
+        let context = QueryContext::new("GET_AUTHOR", ":one");
+        assert_eq!(context.wrap(Error::NotFound).kind(), ErrorKind::NotFound);
+        assert_eq!(
+            context.wrap(Error::decode("id", "invalid length")).kind(),
+            ErrorKind::Decode
+        );
+    }
+
+    #[test]
+    fn test_decode_field() {
+        let error = Error::decode_field("author_id", "Book", "author", "invalid length");
+        assert_eq!(
+            error.to_string(),
+            "could not decode column `author_id` into `Book::author`"
+        );
+        let Error::Decode {
+            column,
+            struct_name,
+            field,
+            ..
+        } = error
+        else {
+            panic!("not a decode error");
+        };
+        assert_eq!(column, "author_id");
+        assert_eq!(struct_name.as_deref(), Some("Book"));
+        assert_eq!(field.as_deref(), Some("author"));
+    }
+
+    #[test]
+    fn test_source_not_in_message() {
+        let error = Error::decode("id", "invalid length");
+        assert_eq!(error.to_string(), "could not decode column `id`");
+        assert_eq!(error.source().unwrap().to_string(), "invalid length");
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+/// Result of an `:execresult` query: the command that was run along with the
+/// number of rows it affected.
+///
+/// The driver only exposes the row count of the command tag, so the command is
+/// taken from the statement when the query is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExecResult {
+    command: &'static str,
+    rows_affected: u64,
+}
+
+impl ExecResult {
+    pub fn new(command: &'static str, rows_affected: u64) -> Self {
+        Self {
+            command,
+            rows_affected,
+        }
+    }
+
+    /// Command name as reported in the PostgreSQL command tag, e.g. `UPDATE`
+    pub fn command(&self) -> &'static str {
+        self.command
+    }
+
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+}
+
+/// Formats the result as a PostgreSQL command tag, e.g. `INSERT 0 1` or `DELETE 3`
+impl fmt::Display for ExecResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.command {
+            "INSERT" => write!(f, "{} 0 {}", self.command, self.rows_affected),
+            _ => write!(f, "{} {}", self.command, self.rows_affected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_tag() {
+        let result = ExecResult::new("DELETE", 3);
+        assert_eq!(result.command(), "DELETE");
+        assert_eq!(result.rows_affected(), 3);
+        assert_eq!(result.to_string(), "DELETE 3");
+        assert_eq!(ExecResult::new("INSERT", 1).to_string(), "INSERT 0 1");
+    }
+}
@@ -1,39 +1,75 @@
+use crate::driver::Row;
 use cfg_block::cfg_block;
+use postgres_types::FromSql;
 use std::collections::HashMap;
 
-#[cfg(feature = "with-postgres")]
-use postgres::Row;
-
-#[cfg(feature = "with-tokio-postgres")]
-use tokio_postgres::Row;
-
+/// Value decoded from a row of either driver, `postgres::Row` being the row type of
+/// `tokio-postgres`
 pub trait FromPostgresRow: Sized {
+    /// Number of columns the value is decoded from
+    const COLUMNS: usize;
+
     fn from_row(row: &Row) -> Result<Self, crate::Error>;
+
+    /// Decodes the value from the columns starting at `offset`, ignoring column names.
+    ///
+    /// Used for table structs embedded with `sqlc.embed()`, whose column names may
+    /// repeat within the row.
+    fn from_row_at(row: &Row, offset: usize) -> Result<Self, crate::Error>;
 }
 
+/// Column type of single column rows, which are decoded from the first column.
+///
+/// Derived for generated enums, `Option<T>` and `Vec<T>` are covered for every
+/// scalar `T`.
+pub trait PostgresScalar: for<'a> FromSql<'a> {}
+
+impl<T: PostgresScalar> FromPostgresRow for T {
+    const COLUMNS: usize = 1;
+
+    fn from_row(row: &Row) -> crate::Result<Self> {
+        Self::from_row_at(row, 0)
+    }
+
+    fn from_row_at(row: &Row, offset: usize) -> crate::Result<Self> {
+        row.try_get::<usize, T>(offset).map_err(|e| {
+            let column = row
+                .columns()
+                .get(offset)
+                .map_or_else(|| offset.to_string(), |column| column.name().to_string());
+            crate::Error::decode(column, e)
+        })
+    }
+}
+
+impl<T: PostgresScalar> PostgresScalar for Option<T> {}
+impl<T: PostgresScalar> PostgresScalar for Vec<T> {}
+
 macro_rules! from_primitive {
     ($t:ty) => {
-        impl FromPostgresRow for $t {
-            fn from_row(row: &Row) -> crate::Result<Self> {
-                Ok(row.try_get::<&str, $t>("0")?)
-            }
-        }
+        impl PostgresScalar for $t {}
     };
 }
 
 from_primitive!(bool);
 from_primitive!(String);
+from_primitive!(i8);
 from_primitive!(i16);
 from_primitive!(i32);
 from_primitive!(i64);
+from_primitive!(u32);
+from_primitive!(f32);
 from_primitive!(f64);
+from_primitive!(Vec<u8>);
 from_primitive!(HashMap<String, Option<String>>);
+from_primitive!(crate::Money);
+from_primitive!(crate::PgInterval);
 
 #[cfg(feature = "with-bit-vec-0_6")]
 from_primitive!(bit_vec_06::BitVec);
 
 #[cfg(feature = "with-uuid-0_8")]
-from_primitive!(uuid_0_8::Uuid);
+from_primitive!(uuid_08::Uuid);
 
 #[cfg(feature = "with-uuid-1")]
 from_primitive!(uuid_1::Uuid);
@@ -47,7 +83,19 @@ from_primitive!(eui48_1::MacAddress);
 #[cfg(feature = "with-serde_json-1")]
 from_primitive!(serde_json_1::Value);
 
+#[cfg(feature = "with-rust_decimal-1")]
+from_primitive!(rust_decimal_1::Decimal);
+
 cfg_block! {
+    #[cfg(feature = "with-chrono-0_4")] {
+        from_primitive!(chrono_04::NaiveDate);
+        from_primitive!(chrono_04::NaiveTime);
+        from_primitive!(chrono_04::NaiveDateTime);
+        from_primitive!(chrono_04::DateTime<chrono_04::Utc>);
+        from_primitive!(chrono_04::DateTime<chrono_04::Local>);
+        from_primitive!(chrono_04::DateTime<chrono_04::FixedOffset>);
+    }
+
     #[cfg(feature = "with-cidr-0_2")] {
         pub use cidr_02::{IpInet, IpCidr};
         from_primitive!(cidr_02::IpInet);
@@ -55,15 +103,15 @@ cfg_block! {
     }
 
     #[cfg(feature = "with-geo-types-0_6")] {
-        from_primitive!(geo_types_06::Point);
-        from_primitive!(geo_types_06::Rect);
-        from_primitive!(geo_types_06::LineString);
+        from_primitive!(geo_types_06::Point<f64>);
+        from_primitive!(geo_types_06::Rect<f64>);
+        from_primitive!(geo_types_06::LineString<f64>);
     }
 
     #[cfg(feature = "with-geo-types-0_7")] {
-        from_primitive!(geo_types_0_7::Point);
-        from_primitive!(geo_types_0_7::Rect);
-        from_primitive!(geo_types_0_7::LineString);
+        from_primitive!(geo_types_0_7::Point<f64>);
+        from_primitive!(geo_types_0_7::Rect<f64>);
+        from_primitive!(geo_types_0_7::LineString<f64>);
     }
 
     #[cfg(feature = "with-time-0_2")] {
@@ -0,0 +1,250 @@
+use bytes::BufMut;
+use postgres_types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+
+const MICROSECONDS_PER_DAY: i64 = 24 * 60 * 60 * 1_000_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IntervalConversionError {
+    #[error("interval has months, which have no fixed duration")]
+    HasMonths,
+
+    #[error("interval is negative")]
+    Negative,
+
+    #[error("interval is out of range")]
+    OutOfRange,
+}
+
+/// PostgreSQL `interval` value. Months and days are kept apart from the
+/// microseconds because their length depends on the date they are applied to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PgInterval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl PgInterval {
+    pub fn new(months: i32, days: i32, microseconds: i64) -> Self {
+        Self {
+            months,
+            days,
+            microseconds,
+        }
+    }
+
+    /// Total microseconds, counting a day as 24 hours
+    fn total_microseconds(&self) -> Result<i64, IntervalConversionError> {
+        if self.months != 0 {
+            return Err(IntervalConversionError::HasMonths);
+        }
+
+        i64::from(self.days)
+            .checked_mul(MICROSECONDS_PER_DAY)
+            .and_then(|days| days.checked_add(self.microseconds))
+            .ok_or(IntervalConversionError::OutOfRange)
+    }
+
+    fn from_microseconds(microseconds: i128) -> Result<Self, IntervalConversionError> {
+        let microseconds =
+            i64::try_from(microseconds).map_err(|_| IntervalConversionError::OutOfRange)?;
+        Ok(Self::new(0, 0, microseconds))
+    }
+}
+
+impl<'a> FromSql<'a> for PgInterval {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        if raw.len() != 16 {
+            return Err(format!("invalid interval length {}", raw.len()).into());
+        }
+
+        Ok(Self::new(
+            i32::from_be_bytes(raw[12..16].try_into()?),
+            i32::from_be_bytes(raw[8..12].try_into()?),
+            i64::from_be_bytes(raw[0..8].try_into()?),
+        ))
+    }
+
+    accepts!(INTERVAL);
+}
+
+impl ToSql for PgInterval {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.put_i64(self.microseconds);
+        out.put_i32(self.days);
+        out.put_i32(self.months);
+        Ok(IsNull::No)
+    }
+
+    accepts!(INTERVAL);
+    to_sql_checked!();
+}
+
+impl TryFrom<PgInterval> for std::time::Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Self, Self::Error> {
+        let microseconds = u64::try_from(interval.total_microseconds()?)
+            .map_err(|_| IntervalConversionError::Negative)?;
+        Ok(std::time::Duration::from_micros(microseconds))
+    }
+}
+
+impl TryFrom<std::time::Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: std::time::Duration) -> Result<Self, Self::Error> {
+        Self::from_microseconds(duration.as_micros() as i128)
+    }
+}
+
+#[cfg(feature = "with-time-0_3")]
+impl TryFrom<PgInterval> for time_03::Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Self, Self::Error> {
+        Ok(time_03::Duration::microseconds(
+            interval.total_microseconds()?,
+        ))
+    }
+}
+
+#[cfg(feature = "with-time-0_3")]
+impl TryFrom<time_03::Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: time_03::Duration) -> Result<Self, Self::Error> {
+        Self::from_microseconds(duration.whole_microseconds())
+    }
+}
+
+#[cfg(feature = "with-chrono-0_4")]
+impl TryFrom<PgInterval> for chrono_04::Duration {
+    type Error = IntervalConversionError;
+
+    fn try_from(interval: PgInterval) -> Result<Self, Self::Error> {
+        Ok(chrono_04::Duration::microseconds(
+            interval.total_microseconds()?,
+        ))
+    }
+}
+
+#[cfg(feature = "with-chrono-0_4")]
+impl TryFrom<chrono_04::Duration> for PgInterval {
+    type Error = IntervalConversionError;
+
+    fn try_from(duration: chrono_04::Duration) -> Result<Self, Self::Error> {
+        let microseconds = duration
+            .num_microseconds()
+            .ok_or(IntervalConversionError::OutOfRange)?;
+        Ok(Self::new(0, 0, microseconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::BytesMut;
+    use std::time::Duration;
+
+    fn round_trip(interval: PgInterval) -> PgInterval {
+        let mut buf = BytesMut::new();
+        interval.to_sql(&Type::INTERVAL, &mut buf).unwrap();
+        PgInterval::from_sql(&Type::INTERVAL, &buf).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for interval in [
+            PgInterval::default(),
+            PgInterval::new(14, 3, 3_723_000_004),
+            PgInterval::new(-14, -3, -3_723_000_004),
+            PgInterval::new(1, -1, 1),
+            PgInterval::new(i32::MAX, i32::MIN, i64::MIN),
+            PgInterval::new(i32::MIN, i32::MAX, i64::MAX),
+        ] {
+            assert_eq!(round_trip(interval), interval);
+        }
+    }
+
+    #[test]
+    fn test_wire_format() {
+        let mut buf = BytesMut::new();
+        PgInterval::new(2, -3, 4)
+            .to_sql(&Type::INTERVAL, &mut buf)
+            .unwrap();
+        assert_eq!(
+            &buf[..],
+            [0, 0, 0, 0, 0, 0, 0, 4, 255, 255, 255, 253, 0, 0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_invalid_length() {
+        assert!(PgInterval::from_sql(&Type::INTERVAL, &[0; 15]).is_err());
+    }
+
+    #[test]
+    fn test_std_duration() {
+        let interval = PgInterval::new(0, 1, 1_500_000);
+        assert_eq!(
+            Duration::try_from(interval).unwrap(),
+            Duration::from_micros(MICROSECONDS_PER_DAY as u64 + 1_500_000)
+        );
+        assert_eq!(
+            PgInterval::try_from(Duration::from_millis(1_500)).unwrap(),
+            PgInterval::new(0, 0, 1_500_000)
+        );
+
+        assert!(matches!(
+            Duration::try_from(PgInterval::new(1, 0, 0)),
+            Err(IntervalConversionError::HasMonths)
+        ));
+        assert!(matches!(
+            Duration::try_from(PgInterval::new(-1, 0, 0)),
+            Err(IntervalConversionError::HasMonths)
+        ));
+        assert!(matches!(
+            Duration::try_from(PgInterval::new(0, -1, 0)),
+            Err(IntervalConversionError::Negative)
+        ));
+        assert!(matches!(
+            Duration::try_from(PgInterval::new(0, i32::MAX, i64::MAX)),
+            Err(IntervalConversionError::OutOfRange)
+        ));
+    }
+
+    #[cfg(feature = "with-time-0_3")]
+    #[test]
+    fn test_time_duration() {
+        let interval = PgInterval::new(0, -1, -1);
+        let duration = time_03::Duration::try_from(interval).unwrap();
+        assert_eq!(
+            duration.whole_microseconds(),
+            -(MICROSECONDS_PER_DAY as i128) - 1
+        );
+        assert_eq!(
+            PgInterval::try_from(duration).unwrap(),
+            PgInterval::new(0, 0, -MICROSECONDS_PER_DAY - 1)
+        );
+        assert!(time_03::Duration::try_from(PgInterval::new(1, 0, 0)).is_err());
+    }
+
+    #[cfg(feature = "with-chrono-0_4")]
+    #[test]
+    fn test_chrono_duration() {
+        let interval = PgInterval::new(0, -1, -1);
+        let duration = chrono_04::Duration::try_from(interval).unwrap();
+        assert_eq!(duration.num_microseconds(), Some(-MICROSECONDS_PER_DAY - 1));
+        assert_eq!(
+            PgInterval::try_from(duration).unwrap(),
+            PgInterval::new(0, 0, -MICROSECONDS_PER_DAY - 1)
+        );
+        assert!(chrono_04::Duration::try_from(PgInterval::new(1, 0, 0)).is_err());
+    }
+}
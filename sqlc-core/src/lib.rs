@@ -1,27 +1,93 @@
 use cfg_block::cfg_block;
 
-#[cfg(all(feature = "with-postgres", feature = "with-tokio-postgres"))]
-compile_error!(
-    "features with-postgres and with-tokio-postgres are mutually exclusive and cannot be enabled together"
-);
-
 #[cfg(all(not(feature = "with-postgres"), not(feature = "with-tokio-postgres")))]
 compile_error!("one of with-postgres and with-tokio-postgres features needs to be enabled");
 
+// `postgres` re-exports the types of `tokio-postgres`, the items of both drivers are
+// named through either crate
+#[cfg(not(feature = "with-tokio-postgres"))]
+use postgres as driver;
+#[cfg(feature = "with-tokio-postgres")]
+use tokio_postgres as driver;
+
 mod dbtx;
 mod error;
+mod exec_result;
 mod from_postgres_row;
+mod interval;
+mod money;
+mod transaction;
 
+pub use driver::Row;
 pub use error::*;
+pub use exec_result::*;
 pub use from_postgres_row::*;
+pub use interval::*;
+pub use money::*;
+pub use postgres_types::Type;
 pub use sqlc_derive::FromPostgresRow;
+pub use transaction::{IsolationLevel, TransactionOptions};
 
 cfg_block! {
     #[cfg(feature = "with-postgres")] {
-        pub use dbtx::pg::*;
+        /// Clients and transactions of the `postgres` crate
+        pub mod sync {
+            pub use crate::dbtx::pg::*;
+            pub use crate::transaction::pg::*;
+        }
     }
 
     #[cfg(feature = "with-tokio-postgres")] {
-        pub use dbtx::tokio_pg::*;
+        /// Clients and transactions of the `tokio-postgres` crate
+        pub mod r#async {
+            pub use crate::dbtx::tokio_pg::*;
+            pub use crate::transaction::tokio_pg::*;
+        }
+    }
+
+    #[cfg(all(feature = "with-postgres", not(feature = "with-tokio-postgres")))] {
+        /// Former path of `sync::DBTX`, implemented by all its implementors
+        #[deprecated(note = "use `sqlc_core::sync::DBTX`")]
+        pub trait DBTX: sync::DBTX {}
+
+        #[allow(deprecated)]
+        impl<C: ?Sized + sync::DBTX> DBTX for C {}
+
+        // the transaction helpers of the only driver are named from the root too, e.g.
+        // `sqlc_core::transaction(&mut client, |tx| ...)`
+        pub use sync::{transaction, transaction_with_options};
+    }
+
+    #[cfg(all(feature = "with-tokio-postgres", not(feature = "with-postgres")))] {
+        /// Former path of `r#async::DBTX`, implemented by all its implementors
+        #[deprecated(note = "use `sqlc_core::r#async::DBTX`")]
+        pub trait DBTX: r#async::DBTX {}
+
+        #[allow(deprecated)]
+        impl<C: ?Sized + r#async::DBTX> DBTX for C {}
+
+        pub use r#async::{
+            transaction, transaction_with_context, transaction_with_options, TransactionFuture,
+        };
+    }
+}
+
+// built with both drivers when the workspace is, its examples enabling one each
+#[cfg(all(test, feature = "with-postgres", feature = "with-tokio-postgres"))]
+mod tests {
+    fn assert_sync_dbtx<C: crate::sync::DBTX>() {}
+
+    fn assert_async_dbtx<C: crate::r#async::DBTX>() {}
+
+    #[test]
+    fn test_both_drivers() {
+        assert_sync_dbtx::<postgres::Client>();
+        assert_sync_dbtx::<postgres::Transaction<'_>>();
+        assert_async_dbtx::<tokio_postgres::Client>();
+        assert_async_dbtx::<tokio_postgres::Transaction<'_>>();
+
+        // rows and errors of both drivers are the same types
+        let _: fn(&postgres::Row) -> &crate::Row = |row| row;
+        let _: fn(postgres::Error) -> crate::Error = crate::Error::from;
     }
 }
@@ -0,0 +1,29 @@
+use postgres_types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+
+/// PostgreSQL `money` value, stored as an integer amount of the smallest
+/// currency unit (e.g. cents for `lc_monetary` set to `en_US`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(pub i64);
+
+impl<'a> FromSql<'a> for Money {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        // money is sent as an int8 over the binary protocol
+        Ok(Money(i64::from_sql(&Type::INT8, raw)?))
+    }
+
+    accepts!(MONEY);
+}
+
+impl ToSql for Money {
+    fn to_sql(
+        &self,
+        _: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.0.to_sql(&Type::INT8, out)
+    }
+
+    accepts!(MONEY);
+    to_sql_checked!();
+}
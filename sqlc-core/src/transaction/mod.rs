@@ -0,0 +1,136 @@
+use crate::ErrorKind;
+use std::time::Duration;
+
+#[cfg(feature = "with-postgres")]
+pub mod pg;
+
+#[cfg(feature = "with-tokio-postgres")]
+pub mod tokio_pg;
+
+pub use crate::driver::IsolationLevel;
+
+/// Settings of the transactions opened by `transaction_with_options`
+///
+/// The transaction is committed when the closure returns `Ok` and rolled back otherwise.
+/// The error of the closure is the one returned, a failed rollback is ignored since the
+/// server discards the transaction anyway.
+///
+/// When the closure or the commit fails with `ErrorKind::SerializationFailure`, i.e. a
+/// serialization failure or a deadlock, the closure is called again in a new transaction,
+/// up to `max_retries` times. The delay before each retry doubles, starting at `backoff`.
+#[derive(Clone, Debug)]
+pub struct TransactionOptions {
+    isolation_level: Option<IsolationLevel>,
+    read_only: Option<bool>,
+    deferrable: Option<bool>,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        Self {
+            isolation_level: None,
+            read_only: None,
+            deferrable: None,
+            max_retries: 3,
+            backoff: Duration::from_millis(10),
+        }
+    }
+}
+
+impl TransactionOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Isolation level of the transaction, the server default when not set
+    pub fn isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = Some(isolation_level);
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = Some(read_only);
+        self
+    }
+
+    /// Only has an effect on serializable read-only transactions
+    pub fn deferrable(mut self, deferrable: bool) -> Self {
+        self.deferrable = Some(deferrable);
+        self
+    }
+
+    /// Times the transaction is run again after a serialization failure or a deadlock, 3 by
+    /// default
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Delay before the first retry, 10ms by default
+    ///
+    /// Async transactions wait with `tokio::time::sleep`, the runtime needs its time driver.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    fn should_retry(&self, retries: u32, kind: ErrorKind) -> bool {
+        retries < self.max_retries && kind == ErrorKind::SerializationFailure
+    }
+
+    fn retry_delay(&self, retries: u32) -> Duration {
+        self.backoff.saturating_mul(1 << retries.min(16))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        let options = TransactionOptions::new();
+        assert!(options.should_retry(0, ErrorKind::SerializationFailure));
+        assert!(!options.should_retry(0, ErrorKind::UniqueViolation));
+        assert!(!options.should_retry(0, ErrorKind::ConnectionLost));
+        assert!(!options.should_retry(0, ErrorKind::Other));
+    }
+
+    #[test]
+    fn test_should_retry_limit() {
+        let options = TransactionOptions::new().max_retries(2);
+        assert!(options.should_retry(1, ErrorKind::SerializationFailure));
+        assert!(!options.should_retry(2, ErrorKind::SerializationFailure));
+
+        let options = TransactionOptions::new().max_retries(0);
+        assert!(!options.should_retry(0, ErrorKind::SerializationFailure));
+    }
+
+    #[test]
+    fn test_retried_sql_states() {
+        use crate::driver::error::SqlState;
+
+        let options = TransactionOptions::new();
+        for code in [
+            SqlState::T_R_SERIALIZATION_FAILURE,
+            SqlState::T_R_DEADLOCK_DETECTED,
+        ] {
+            assert!(options.should_retry(0, ErrorKind::from_sql_state(&code)));
+        }
+        assert!(!options.should_retry(0, ErrorKind::from_sql_state(&SqlState::UNIQUE_VIOLATION)));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let options = TransactionOptions::new().backoff(Duration::from_millis(5));
+        assert_eq!(options.retry_delay(0), Duration::from_millis(5));
+        assert_eq!(options.retry_delay(1), Duration::from_millis(10));
+        assert_eq!(options.retry_delay(3), Duration::from_millis(40));
+        assert_eq!(
+            options.retry_delay(u32::MAX),
+            Duration::from_millis(5 << 16)
+        );
+    }
+}
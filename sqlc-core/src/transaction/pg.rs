@@ -0,0 +1,59 @@
+use crate::{Result, TransactionOptions};
+use postgres::{Client, Transaction};
+
+/// Runs `f` in a transaction, committed and retried as described by `TransactionOptions`
+pub fn transaction<T, F>(client: &mut Client, f: F) -> Result<T>
+where
+    F: FnMut(&mut Transaction<'_>) -> Result<T>,
+{
+    transaction_with_options(client, &TransactionOptions::default(), f)
+}
+
+/// Runs `f` in a transaction opened with `options`
+pub fn transaction_with_options<T, F>(
+    client: &mut Client,
+    options: &TransactionOptions,
+    mut f: F,
+) -> Result<T>
+where
+    F: FnMut(&mut Transaction<'_>) -> Result<T>,
+{
+    let mut retries = 0;
+    loop {
+        match run_once(client, options, &mut f) {
+            Err(e) if options.should_retry(retries, e.kind()) => {
+                std::thread::sleep(options.retry_delay(retries));
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn run_once<T, F>(client: &mut Client, options: &TransactionOptions, f: &mut F) -> Result<T>
+where
+    F: FnMut(&mut Transaction<'_>) -> Result<T>,
+{
+    let mut builder = client.build_transaction();
+    if let Some(isolation_level) = options.isolation_level {
+        builder = builder.isolation_level(isolation_level);
+    }
+    if let Some(read_only) = options.read_only {
+        builder = builder.read_only(read_only);
+    }
+    if let Some(deferrable) = options.deferrable {
+        builder = builder.deferrable(deferrable);
+    }
+    let mut tx = builder.start()?;
+
+    match f(&mut tx) {
+        Ok(value) => {
+            tx.commit()?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = tx.rollback();
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{Result, TransactionOptions};
+use std::future::Future;
+use std::pin::Pin;
+use tokio_postgres::{Client, Transaction};
+
+/// Future returned by the closure run in a transaction, borrowing the transaction and the
+/// context
+pub type TransactionFuture<'t, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 't>>;
+
+/// Runs `f` in a transaction, committed and retried as described by `TransactionOptions`
+///
+/// The future borrows the transaction, so it is returned boxed:
+/// `|tx| Box::pin(async move { ... })`. Values of the caller it borrows are passed with
+/// `transaction_with_context`.
+pub async fn transaction<T, F>(client: &mut Client, mut f: F) -> Result<T>
+where
+    F: for<'t> FnMut(&'t mut Transaction<'_>) -> TransactionFuture<'t, T>,
+{
+    let options = TransactionOptions::default();
+    transaction_with_context(client, &options, &mut (), |tx, _| f(tx)).await
+}
+
+/// Runs `f` in a transaction opened with `options`
+pub async fn transaction_with_options<T, F>(
+    client: &mut Client,
+    options: &TransactionOptions,
+    mut f: F,
+) -> Result<T>
+where
+    F: for<'t> FnMut(&'t mut Transaction<'_>) -> TransactionFuture<'t, T>,
+{
+    transaction_with_context(client, options, &mut (), |tx, _| f(tx)).await
+}
+
+/// Runs `f` in a transaction opened with `options`, passing it `context` along with the
+/// transaction
+///
+/// The future may borrow the context for as long as the transaction, so that it uses the
+/// values of the caller without cloning them, e.g. with a tuple of references:
+/// `|tx, (params, ids)| Box::pin(async move { ... })`.
+pub async fn transaction_with_context<C, T, F>(
+    client: &mut Client,
+    options: &TransactionOptions,
+    context: &mut C,
+    mut f: F,
+) -> Result<T>
+where
+    C: ?Sized,
+    F: for<'t> FnMut(&'t mut Transaction<'_>, &'t mut C) -> TransactionFuture<'t, T>,
+{
+    let mut retries = 0;
+    loop {
+        match run_once(client, options, context, &mut f).await {
+            Err(e) if options.should_retry(retries, e.kind()) => {
+                tokio::time::sleep(options.retry_delay(retries)).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn run_once<C, T, F>(
+    client: &mut Client,
+    options: &TransactionOptions,
+    context: &mut C,
+    f: &mut F,
+) -> Result<T>
+where
+    C: ?Sized,
+    F: for<'t> FnMut(&'t mut Transaction<'_>, &'t mut C) -> TransactionFuture<'t, T>,
+{
+    let mut builder = client.build_transaction();
+    if let Some(isolation_level) = options.isolation_level {
+        builder = builder.isolation_level(isolation_level);
+    }
+    if let Some(read_only) = options.read_only {
+        builder = builder.read_only(read_only);
+    }
+    if let Some(deferrable) = options.deferrable {
+        builder = builder.deferrable(deferrable);
+    }
+    let mut tx = builder.start().await?;
+
+    let result = f(&mut tx, context).await;
+    match result {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = tx.rollback().await;
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Backend speaking the PostgreSQL protocol on a local port, recording the statements the
+//! transaction helpers send
+
+use bytes::{BufMut, BytesMut};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Backend accepting a single connection
+pub struct Backend {
+    pub port: u16,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Backend {
+    /// Starts a backend failing each of `failures` once with a serialization failure, in
+    /// the order they are sent
+    pub fn start(failures: &[&str]) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let failures = failures.iter().map(|s| s.to_string()).collect();
+
+        let statements = log.clone();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve(stream, failures, statements);
+        });
+
+        Self { port, log }
+    }
+
+    /// Statements received so far
+    pub fn log(&self) -> Vec<String> {
+        self.log.lock().unwrap().clone()
+    }
+}
+
+/// Answers the startup and simple query messages of a connection
+fn serve(mut stream: TcpStream, mut failures: Vec<String>, log: Arc<Mutex<Vec<String>>>) {
+    let len = read_i32(&mut stream);
+    let mut startup = vec![0; len as usize - 4];
+    stream.read_exact(&mut startup).unwrap();
+
+    let mut out = BytesMut::new();
+    message(&mut out, b'R', |buf| buf.put_i32(0));
+    message(&mut out, b'Z', |buf| buf.put_u8(b'I'));
+    stream.write_all(&out).unwrap();
+
+    loop {
+        let mut tag = [0];
+        if stream.read_exact(&mut tag).is_err() {
+            return;
+        }
+        let len = read_i32(&mut stream);
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).unwrap();
+
+        out.clear();
+        match tag[0] {
+            // simple query
+            b'Q' => {
+                let query = String::from_utf8(body[..body.len() - 1].to_vec()).unwrap();
+                log.lock().unwrap().push(query.clone());
+
+                if let Some(i) = failures.iter().position(|f| *f == query) {
+                    failures.remove(i);
+                    message(&mut out, b'E', |buf| {
+                        for (field, value) in [
+                            (b'S', "ERROR"),
+                            (b'C', "40001"),
+                            (b'M', "could not serialize access"),
+                        ] {
+                            buf.put_u8(field);
+                            buf.put_slice(value.as_bytes());
+                            buf.put_u8(0);
+                        }
+                        buf.put_u8(0);
+                    });
+                    message(&mut out, b'Z', |buf| buf.put_u8(b'E'));
+                } else {
+                    let command = query.split(' ').next().unwrap();
+                    message(&mut out, b'C', |buf| {
+                        buf.put_slice(command.as_bytes());
+                        buf.put_u8(0);
+                    });
+                    message(&mut out, b'Z', |buf| buf.put_u8(b'T'));
+                }
+                stream.write_all(&out).unwrap();
+            }
+            // terminate
+            b'X' => return,
+            tag => panic!("unexpected message {}", tag as char),
+        }
+    }
+}
+
+fn read_i32(stream: &mut TcpStream) -> i32 {
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).unwrap();
+    i32::from_be_bytes(buf)
+}
+
+/// Writes a backend message, its length preceding the body
+fn message(out: &mut BytesMut, tag: u8, body: impl FnOnce(&mut BytesMut)) {
+    out.put_u8(tag);
+    let start = out.len();
+    out.put_i32(0);
+    body(out);
+    let len = (out.len() - start) as i32;
+    out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+}
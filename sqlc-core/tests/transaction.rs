@@ -0,0 +1,251 @@
+mod common;
+
+use common::Backend;
+use sqlc_core::{Error, ErrorKind, IsolationLevel, TransactionOptions};
+use std::time::Duration;
+
+fn retry_options() -> TransactionOptions {
+    TransactionOptions::new()
+        .isolation_level(IsolationLevel::Serializable)
+        .max_retries(1)
+        .backoff(Duration::from_millis(1))
+}
+
+#[cfg(feature = "with-postgres")]
+mod sync {
+    use super::*;
+    use sqlc_core::sync::{transaction, transaction_with_options};
+
+    fn connect(backend: &Backend) -> postgres::Client {
+        postgres::Config::new()
+            .host("127.0.0.1")
+            .port(backend.port)
+            .user("sqlc")
+            .connect(postgres::NoTls)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_commit() {
+        let backend = Backend::start(&[]);
+        let mut client = connect(&backend);
+
+        let result = transaction(&mut client, |tx| {
+            tx.batch_execute("UPDATE book")?;
+            Ok(1)
+        });
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(
+            backend.log(),
+            ["START TRANSACTION", "UPDATE book", "COMMIT"]
+        );
+    }
+
+    #[test]
+    fn test_rollback() {
+        let backend = Backend::start(&[]);
+        let mut client = connect(&backend);
+
+        let result: sqlc_core::Result<()> = transaction(&mut client, |tx| {
+            tx.batch_execute("DELETE FROM book")?;
+            Err(Error::NotFound)
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(
+            backend.log(),
+            ["START TRANSACTION", "DELETE FROM book", "ROLLBACK"]
+        );
+    }
+
+    #[test]
+    fn test_retry() {
+        let backend = Backend::start(&["UPDATE book", "COMMIT"]);
+        let mut client = connect(&backend);
+
+        let mut attempts = 0;
+        let result = transaction_with_options(&mut client, &retry_options(), |tx| {
+            attempts += 1;
+            tx.batch_execute("UPDATE book")?;
+            Ok(attempts)
+        });
+        // the commit of the retried transaction fails too, and no retry is left
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::SerializationFailure);
+        assert_eq!(attempts, 2);
+        assert_eq!(
+            backend.log(),
+            [
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "ROLLBACK",
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_retry_commit() {
+        let backend = Backend::start(&["COMMIT"]);
+        let mut client = connect(&backend);
+
+        let result = transaction_with_options(&mut client, &retry_options(), |tx| {
+            tx.batch_execute("UPDATE book")?;
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(
+            backend.log(),
+            [
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "COMMIT",
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "COMMIT",
+            ]
+        );
+    }
+}
+
+#[cfg(feature = "with-tokio-postgres")]
+mod r#async {
+    use super::*;
+    use sqlc_core::r#async::{transaction, transaction_with_context, transaction_with_options};
+    use std::future::Future;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    async fn connect(backend: &Backend) -> tokio_postgres::Client {
+        let (client, connection) = tokio_postgres::Config::new()
+            .host("127.0.0.1")
+            .port(backend.port)
+            .user("sqlc")
+            .connect(tokio_postgres::NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+        client
+    }
+
+    #[test]
+    fn test_commit() {
+        let backend = Backend::start(&[]);
+        let result = block_on(async {
+            let mut client = connect(&backend).await;
+            transaction(&mut client, |tx| {
+                Box::pin(async move {
+                    tx.batch_execute("UPDATE book").await?;
+                    Ok(1)
+                })
+            })
+            .await
+        });
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(
+            backend.log(),
+            ["START TRANSACTION", "UPDATE book", "COMMIT"]
+        );
+    }
+
+    #[test]
+    fn test_rollback() {
+        let backend = Backend::start(&[]);
+        let result: sqlc_core::Result<()> = block_on(async {
+            let mut client = connect(&backend).await;
+            transaction(&mut client, |tx| {
+                Box::pin(async move {
+                    tx.batch_execute("DELETE FROM book").await?;
+                    Err(Error::NotFound)
+                })
+            })
+            .await
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(
+            backend.log(),
+            ["START TRANSACTION", "DELETE FROM book", "ROLLBACK"]
+        );
+    }
+
+    #[test]
+    fn test_retry() {
+        let backend = Backend::start(&["UPDATE book", "COMMIT"]);
+        let result: sqlc_core::Result<()> = block_on(async {
+            let mut client = connect(&backend).await;
+            transaction_with_options(&mut client, &retry_options(), |tx| {
+                Box::pin(async move {
+                    tx.batch_execute("UPDATE book").await?;
+                    Ok(())
+                })
+            })
+            .await
+        });
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::SerializationFailure);
+        assert_eq!(
+            backend.log(),
+            [
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "ROLLBACK",
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context() {
+        let backend = Backend::start(&["COMMIT"]);
+        let titles = vec!["Dune".to_string(), "Emma".to_string()];
+        let mut attempts = 0;
+
+        let result = block_on(async {
+            let mut client = connect(&backend).await;
+            let options = retry_options();
+            let mut context = (&titles, &mut attempts);
+            let future = transaction_with_context(
+                &mut client,
+                &options,
+                &mut context,
+                |tx, (titles, attempts)| {
+                    Box::pin(async move {
+                        **attempts += 1;
+                        for title in titles.iter() {
+                            tx.batch_execute(&format!("UPDATE book SET title = '{title}'"))
+                                .await?;
+                        }
+                        Ok(titles.len())
+                    })
+                },
+            );
+            // the future borrowing the context can still be spawned
+            assert_send(&future);
+            future.await
+        });
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(attempts, 2);
+        assert_eq!(
+            backend.log(),
+            [
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book SET title = 'Dune'",
+                "UPDATE book SET title = 'Emma'",
+                "COMMIT",
+                "START TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+                "UPDATE book SET title = 'Dune'",
+                "UPDATE book SET title = 'Emma'",
+                "COMMIT",
+            ]
+        );
+    }
+}
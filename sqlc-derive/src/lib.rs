@@ -1,49 +1,320 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::format_ident;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
+use syn::spanned::Spanned;
 use syn::{parse_macro_input, DeriveInput};
 
-#[cfg(all(feature = "with-postgres", feature = "with-tokio-postgres"))]
-compile_error!(
-    "with-postgres and with-tokio-postgres are mutually exclusive and cannot be enabled together"
-);
+/// Options given with `#[sqlc(...)]` on a field
+#[derive(Default)]
+struct FieldAttrs {
+    index: Option<usize>,
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+    embed: bool,
+    try_from: Option<syn::Type>,
+}
 
-#[proc_macro_derive(FromPostgresRow)]
-pub fn from_postgres_row(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let DeriveInput { ident, data, .. } = input;
-
-    let fields = match data {
-        syn::Data::Struct(data_struct) => data_struct.fields.into_iter().map(|field| {
-            let ident_field_name = field.ident.clone().unwrap();
-            let field_type = field.ty;
-            let field_name = field.ident.unwrap().to_string();
-            quote! {
-                #ident_field_name: row.try_get::<&str, #field_type>(#field_name)?
+impl FieldAttrs {
+    fn from_field(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("sqlc"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("index") {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    attrs.index = Some(lit.base10_parse()?);
+                } else if meta.path.is_ident("rename") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    attrs.rename = Some(lit.value());
+                } else if meta.path.is_ident("default") {
+                    attrs.default = true;
+                } else if meta.path.is_ident("skip") {
+                    attrs.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    attrs.flatten = true;
+                } else if meta.path.is_ident("embed") {
+                    attrs.embed = true;
+                } else if meta.path.is_ident("try_from") {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    attrs.try_from = Some(lit.parse()?);
+                } else {
+                    return Err(meta.error("unsupported sqlc attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let column_attrs = attrs.index.is_some()
+            || attrs.rename.is_some()
+            || attrs.default
+            || attrs.try_from.is_some();
+        if attrs.index.is_some() && attrs.rename.is_some() {
+            return Err(syn::Error::new(
+                field.span(),
+                "`index` and `rename` cannot be used together",
+            ));
+        }
+        if attrs.skip && (column_attrs || attrs.flatten || attrs.embed) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`skip` cannot be combined with other sqlc attributes",
+            ));
+        }
+        if attrs.flatten && (column_attrs || attrs.embed) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`flatten` cannot be combined with other sqlc attributes",
+            ));
+        }
+        if attrs.embed && column_attrs {
+            return Err(syn::Error::new(
+                field.span(),
+                "`embed` cannot be combined with other sqlc attributes",
+            ));
+        }
+
+        Ok(attrs)
+    }
+
+    /// Number of columns the field is decoded from
+    fn columns(&self, field_type: &syn::Type) -> Columns {
+        if self.skip {
+            Columns::default()
+        } else if self.flatten || self.embed {
+            Columns {
+                fixed: 0,
+                nested: vec![quote! { <#field_type as ::sqlc_core::FromPostgresRow>::COLUMNS }],
             }
-        }),
-        _ => unimplemented!(),
+        } else {
+            Columns {
+                fixed: 1,
+                nested: vec![],
+            }
+        }
+    }
+}
+
+/// Column count known partly at expansion time and partly through nested `COLUMNS`
+#[derive(Default, Clone)]
+struct Columns {
+    fixed: usize,
+    nested: Vec<TokenStream2>,
+}
+
+impl Columns {
+    fn add(&mut self, other: Columns) {
+        self.fixed += other.fixed;
+        self.nested.extend(other.nested);
+    }
+
+    /// Column position after `base`, leaving out zero terms
+    fn after(&self, base: Option<&TokenStream2>) -> TokenStream2 {
+        let mut terms = base.into_iter().cloned().collect::<Vec<_>>();
+        terms.extend(self.nested.iter().cloned());
+        if self.fixed > 0 || terms.is_empty() {
+            let fixed = self.fixed;
+            terms.push(quote! { #fixed });
+        }
+        quote! { #(#terms)+* }
+    }
+}
+
+/// Expression reading a single field from `row`
+///
+/// `offset` is the first column of the struct when it is decoded by position, in which
+/// case column names are ignored. `columns` counts the columns before the field.
+/// `struct_name` and `field_name` name the field in decode errors.
+fn field_value(
+    field: &syn::Field,
+    struct_name: &str,
+    field_name: &str,
+    attrs: &FieldAttrs,
+    columns: &Columns,
+    offset: Option<&TokenStream2>,
+) -> TokenStream2 {
+    let field_type = &field.ty;
+    let position = columns.after(offset);
+
+    if attrs.skip {
+        return quote! { ::std::default::Default::default() };
+    }
+
+    if attrs.embed || (attrs.flatten && offset.is_some()) {
+        return quote! {
+            <#field_type as ::sqlc_core::FromPostgresRow>::from_row_at(row, #position)?
+        };
+    }
+
+    if attrs.flatten {
+        return quote! {
+            <#field_type as ::sqlc_core::FromPostgresRow>::from_row(row)?
+        };
+    }
+
+    // columns are looked up by name, except for tuple struct fields without a rename
+    let name = attrs
+        .rename
+        .clone()
+        .or_else(|| field.ident.as_ref().map(|ident| ident.to_string()));
+    let (key, has_column, column) = match (attrs.index, offset, name) {
+        (Some(index), _, _) => {
+            let key = Columns {
+                fixed: index,
+                nested: vec![],
+            }
+            .after(offset);
+            (key.clone(), quote! { row.len() > #key }, index.to_string())
+        }
+        (None, None, Some(name)) => (
+            quote! { #name },
+            quote! { row.columns().iter().any(|column| column.name() == #name) },
+            name,
+        ),
+        (None, _, name) => (
+            position.clone(),
+            quote! { row.len() > #position },
+            name.unwrap_or_else(|| position.to_string()),
+        ),
     };
 
-    let module;
-    if cfg!(feature = "with-postgres") {
-        module = format_ident!("postgres");
-    } else if cfg!(feature = "with-tokio-postgres") {
-        module = format_ident!("tokio_postgres");
-    } else if cfg!(feature = "with-deadpool") {
-        module = format_ident!("tokio_postgres");
+    let value = match &attrs.try_from {
+        Some(source_type) => quote! {
+            <#field_type as ::std::convert::TryFrom<#source_type>>::try_from(
+                row.try_get::<_, #source_type>(#key)
+                    .map_err(|e| ::sqlc_core::Error::decode_field(#column, #struct_name, #field_name, e))?,
+            )
+            .map_err(|e| ::sqlc_core::Error::decode_field(#column, #struct_name, #field_name, e))?
+        },
+        None => quote! {
+            row.try_get::<_, #field_type>(#key)
+                .map_err(|e| ::sqlc_core::Error::decode_field(#column, #struct_name, #field_name, e))?
+        },
+    };
+
+    if attrs.default {
+        quote! {
+            if #has_column {
+                #value
+            } else {
+                ::std::default::Default::default()
+            }
+        }
     } else {
-        panic!("one of (with-postgres, with-tokio-postgres, with-deadpool) must be enabled");
+        value
     }
+}
+
+/// Decoded struct, by column name in `from_row` and by position in `from_row_at`
+struct StructBody {
+    by_name: TokenStream2,
+    by_position: TokenStream2,
+    columns: TokenStream2,
+}
+
+fn struct_body(ident: &syn::Ident, fields: &syn::Fields) -> syn::Result<StructBody> {
+    let attrs = fields
+        .iter()
+        .map(FieldAttrs::from_field)
+        .collect::<syn::Result<Vec<_>>>()?;
 
+    let offset = quote! { offset };
+    let mut columns = Columns::default();
+    let mut by_name = vec![];
+    let mut by_position = vec![];
+    for (index, (field, attrs)) in fields.iter().zip(&attrs).enumerate() {
+        let struct_name = ident.to_string();
+        let field_name = match &field.ident {
+            Some(field_ident) => field_ident.to_string(),
+            None => index.to_string(),
+        };
+        by_name.push(field_value(
+            field,
+            &struct_name,
+            &field_name,
+            attrs,
+            &columns,
+            None,
+        ));
+        by_position.push(field_value(
+            field,
+            &struct_name,
+            &field_name,
+            attrs,
+            &columns,
+            Some(&offset),
+        ));
+        columns.add(attrs.columns(&field.ty));
+    }
+
+    let construct = |values: Vec<TokenStream2>| match fields {
+        syn::Fields::Named(_) => {
+            let idents = fields.iter().map(|field| &field.ident);
+            quote! { Self { #(#idents: #values),* } }
+        }
+        syn::Fields::Unnamed(_) => quote! { Self(#(#values),*) },
+        syn::Fields::Unit => quote! { Self },
+    };
+
+    Ok(StructBody {
+        by_name: construct(by_name),
+        by_position: construct(by_position),
+        columns: columns.after(None),
+    })
+}
+
+#[proc_macro_derive(FromPostgresRow, attributes(sqlc))]
+pub fn from_postgres_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        ..
+    } = input;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &data {
+        syn::Data::Struct(data_struct) => struct_body(&ident, &data_struct.fields),
+        // enums are column types, decoded from the first column of single column rows
+        syn::Data::Enum(_) => {
+            return TokenStream::from(quote! {
+                impl #impl_generics ::sqlc_core::PostgresScalar for #ident #ty_generics
+                    #where_clause {}
+            });
+        }
+        syn::Data::Union(data_union) => Err(syn::Error::new(
+            data_union.union_token.span,
+            "FromPostgresRow cannot be derived for unions",
+        )),
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let StructBody {
+        by_name,
+        by_position,
+        columns,
+    } = body;
     let expanded = quote! {
-        impl ::sqlc_core::FromPostgresRow for #ident {
-            fn from_row(row: &#module::Row) -> ::sqlc_core::Result<Self> {
-                Ok(Self {
-                    #(#fields),*
-                })
+        impl #impl_generics ::sqlc_core::FromPostgresRow for #ident #ty_generics #where_clause {
+            const COLUMNS: usize = #columns;
+
+            fn from_row(row: &::sqlc_core::Row) -> ::sqlc_core::Result<Self> {
+                Ok(#by_name)
+            }
+
+            #[allow(unused_variables)]
+            fn from_row_at(row: &::sqlc_core::Row, offset: usize) -> ::sqlc_core::Result<Self> {
+                Ok(#by_position)
             }
         }
     };
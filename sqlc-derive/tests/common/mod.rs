@@ -0,0 +1,133 @@
+//! Rows served by an in-memory backend speaking the PostgreSQL protocol, so that
+//! derived impls decode the same `Row`s a server would send
+
+use bytes::{BufMut, BytesMut};
+use postgres_types::{IsNull, ToSql, Type};
+use sqlc_core::Row;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+/// Column of a mocked row: its name, type and value
+pub type Column<'a> = (&'a str, Type, &'a (dyn ToSql + Sync));
+
+/// Row with the given columns, as returned by a query
+pub fn row(columns: &[Column]) -> Row {
+    let fields = columns
+        .iter()
+        .map(|(name, typ, _)| (name.to_string(), typ.clone()))
+        .collect::<Vec<_>>();
+    let values = columns
+        .iter()
+        .map(|(_, typ, value)| {
+            let mut buf = BytesMut::new();
+            match value.to_sql_checked(typ, &mut buf).unwrap() {
+                IsNull::Yes => None,
+                IsNull::No => Some(buf.to_vec()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let (stream, backend) = tokio::io::duplex(4096);
+        tokio::spawn(serve(backend, fields, values));
+
+        let (client, connection) = tokio_postgres::Config::new()
+            .user("sqlc")
+            .connect_raw(stream, tokio_postgres::NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+
+        let stmt = client.prepare("SELECT").await.unwrap();
+        client.query_one(&stmt, &[]).await.unwrap()
+    })
+}
+
+/// Answers the startup and extended query messages of a single row query
+async fn serve(
+    mut stream: DuplexStream,
+    fields: Vec<(String, Type)>,
+    values: Vec<Option<Vec<u8>>>,
+) {
+    let len = stream.read_i32().await.unwrap();
+    let mut startup = vec![0; len as usize - 4];
+    stream.read_exact(&mut startup).await.unwrap();
+
+    let mut out = BytesMut::new();
+    message(&mut out, b'R', |buf| buf.put_i32(0));
+    message(&mut out, b'Z', |buf| buf.put_u8(b'I'));
+    stream.write_all(&out).await.unwrap();
+    out.clear();
+
+    loop {
+        let Ok(tag) = stream.read_u8().await else {
+            return;
+        };
+        let len = stream.read_i32().await.unwrap();
+        let mut body = vec![0; len as usize - 4];
+        stream.read_exact(&mut body).await.unwrap();
+
+        match tag {
+            // parse
+            b'P' => message(&mut out, b'1', |_| {}),
+            // describe
+            b'D' => {
+                message(&mut out, b't', |buf| buf.put_i16(0));
+                message(&mut out, b'T', |buf| {
+                    buf.put_i16(fields.len() as i16);
+                    for (name, typ) in &fields {
+                        buf.put_slice(name.as_bytes());
+                        buf.put_u8(0);
+                        buf.put_i32(0);
+                        buf.put_i16(0);
+                        buf.put_u32(typ.oid());
+                        buf.put_i16(-1);
+                        buf.put_i32(-1);
+                        buf.put_i16(0);
+                    }
+                });
+            }
+            // bind
+            b'B' => message(&mut out, b'2', |_| {}),
+            // execute
+            b'E' => {
+                message(&mut out, b'D', |buf| {
+                    buf.put_i16(values.len() as i16);
+                    for value in &values {
+                        match value {
+                            Some(value) => {
+                                buf.put_i32(value.len() as i32);
+                                buf.put_slice(value);
+                            }
+                            None => buf.put_i32(-1),
+                        }
+                    }
+                });
+                message(&mut out, b'C', |buf| buf.put_slice(b"SELECT 1\0"));
+            }
+            // close
+            b'C' => message(&mut out, b'3', |_| {}),
+            // sync
+            b'S' => {
+                message(&mut out, b'Z', |buf| buf.put_u8(b'I'));
+                stream.write_all(&out).await.unwrap();
+                out.clear();
+            }
+            // terminate
+            b'X' => return,
+            _ => panic!("unexpected message {}", tag as char),
+        }
+    }
+}
+
+/// Writes a backend message, its length preceding the body
+fn message(out: &mut BytesMut, tag: u8, body: impl FnOnce(&mut BytesMut)) {
+    out.put_u8(tag);
+    let start = out.len();
+    out.put_i32(0);
+    body(out);
+    let len = (out.len() - start) as i32;
+    out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+}
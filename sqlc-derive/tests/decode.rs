@@ -0,0 +1,352 @@
+mod common;
+
+use common::row;
+use postgres_types::Type;
+use sqlc_core::FromPostgresRow;
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct Author {
+    id: i64,
+    #[sqlc(rename = "author_name")]
+    name: String,
+}
+
+#[test]
+fn test_rename() {
+    // columns are looked up by name, whatever their position
+    let row = row(&[
+        ("author_name", Type::TEXT, &"Ursula"),
+        ("name", Type::TEXT, &"unused"),
+        ("id", Type::INT8, &1i64),
+    ]);
+    assert_eq!(
+        Author::from_row(&row).unwrap(),
+        Author {
+            id: 1,
+            name: "Ursula".to_string(),
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct AuthorBio {
+    id: i64,
+    #[sqlc(default)]
+    bio: Option<String>,
+    #[sqlc(default, rename = "author_rank")]
+    rank: i32,
+}
+
+#[test]
+fn test_default() {
+    let ranked = row(&[
+        ("id", Type::INT8, &1i64),
+        ("author_rank", Type::INT4, &3i32),
+    ]);
+    assert_eq!(
+        AuthorBio::from_row(&ranked).unwrap(),
+        AuthorBio {
+            id: 1,
+            bio: None,
+            rank: 3,
+        }
+    );
+
+    let with_bio = row(&[("id", Type::INT8, &1i64), ("bio", Type::TEXT, &"Writer")]);
+    assert_eq!(
+        AuthorBio::from_row(&with_bio).unwrap(),
+        AuthorBio {
+            id: 1,
+            bio: Some("Writer".to_string()),
+            rank: 0,
+        }
+    );
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Cache(Vec<i64>);
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct CachedAuthor {
+    #[sqlc(skip)]
+    cache: Cache,
+    id: i64,
+}
+
+#[test]
+fn test_skip() {
+    assert_eq!(<CachedAuthor as FromPostgresRow>::COLUMNS, 1);
+
+    // skipped fields take up no column when decoding by position
+    let row = row(&[("id", Type::INT8, &7i64)]);
+    let expected = CachedAuthor {
+        cache: Cache::default(),
+        id: 7,
+    };
+    assert_eq!(CachedAuthor::from_row(&row).unwrap(), expected);
+    assert_eq!(CachedAuthor::from_row_at(&row, 0).unwrap(), expected);
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct Audit {
+    created_by: String,
+    updated_by: String,
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct AuditedAuthor {
+    id: i64,
+    #[sqlc(flatten)]
+    audit: Audit,
+    name: String,
+}
+
+#[test]
+fn test_flatten() {
+    assert_eq!(<AuditedAuthor as FromPostgresRow>::COLUMNS, 4);
+
+    let expected = AuditedAuthor {
+        id: 1,
+        audit: Audit {
+            created_by: "alice".to_string(),
+            updated_by: "bob".to_string(),
+        },
+        name: "Ursula".to_string(),
+    };
+    let by_name = row(&[
+        ("updated_by", Type::TEXT, &"bob"),
+        ("name", Type::TEXT, &"Ursula"),
+        ("created_by", Type::TEXT, &"alice"),
+        ("id", Type::INT8, &1i64),
+    ]);
+    assert_eq!(AuditedAuthor::from_row(&by_name).unwrap(), expected);
+
+    let by_position = row(&[
+        ("id", Type::INT8, &1i64),
+        ("created_by", Type::TEXT, &"alice"),
+        ("updated_by", Type::TEXT, &"bob"),
+        ("name", Type::TEXT, &"Ursula"),
+    ]);
+    assert_eq!(
+        AuditedAuthor::from_row_at(&by_position, 0).unwrap(),
+        expected
+    );
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct RankedAuthor {
+    #[sqlc(try_from = "i64")]
+    id: u16,
+    #[sqlc(try_from = "i32", rename = "author_rank")]
+    rank: u8,
+}
+
+#[test]
+fn test_try_from() {
+    let row_with = |id: &i64| row(&[("id", Type::INT8, id), ("author_rank", Type::INT4, &2i32)]);
+    assert_eq!(
+        RankedAuthor::from_row(&row_with(&300)).unwrap(),
+        RankedAuthor { id: 300, rank: 2 }
+    );
+
+    let error = RankedAuthor::from_row(&row_with(&-1)).unwrap_err();
+    let sqlc_core::Error::Decode {
+        column,
+        struct_name,
+        field,
+        ..
+    } = error
+    else {
+        panic!("expected a decode error, got {error:?}");
+    };
+    assert_eq!(column, "id");
+    assert_eq!(struct_name.as_deref(), Some("RankedAuthor"));
+    assert_eq!(field.as_deref(), Some("id"));
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct AuthorId(i64);
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct AuthorTuple(i64, #[sqlc(rename = "name")] String, Option<String>);
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct Marker;
+
+#[test]
+fn test_tuple() {
+    assert_eq!(<AuthorTuple as FromPostgresRow>::COLUMNS, 3);
+    assert_eq!(<Marker as FromPostgresRow>::COLUMNS, 0);
+
+    // unnamed fields are read by position, renamed ones by name
+    let row = row(&[
+        ("id", Type::INT8, &1i64),
+        ("name", Type::TEXT, &"Ursula"),
+        ("bio", Type::TEXT, &None::<String>),
+    ]);
+    assert_eq!(AuthorId::from_row(&row).unwrap(), AuthorId(1));
+    assert_eq!(
+        AuthorTuple::from_row(&row).unwrap(),
+        AuthorTuple(1, "Ursula".to_string(), None)
+    );
+    assert_eq!(Marker::from_row(&row).unwrap(), Marker);
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct BookByIndex {
+    #[sqlc(index = 1)]
+    title: String,
+    #[sqlc(index = 0)]
+    id: i32,
+}
+
+#[test]
+fn test_index() {
+    // column names are ignored, the index is counted from the first column of the struct
+    let row = row(&[
+        ("title", Type::INT4, &5i32),
+        ("id", Type::TEXT, &"Dune"),
+        ("rank", Type::INT4, &9i32),
+        ("note", Type::TEXT, &"Solaris"),
+    ]);
+    assert_eq!(
+        BookByIndex::from_row(&row).unwrap(),
+        BookByIndex {
+            title: "Dune".to_string(),
+            id: 5,
+        }
+    );
+    assert_eq!(
+        BookByIndex::from_row_at(&row, 2).unwrap(),
+        BookByIndex {
+            title: "Solaris".to_string(),
+            id: 9,
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct Review {
+    id: i32,
+    #[sqlc(embed)]
+    audit: Audit,
+    #[sqlc(flatten)]
+    author: Author,
+    #[sqlc(skip)]
+    cache: Cache,
+    score: i32,
+}
+
+#[test]
+fn test_offsets() {
+    assert_eq!(<Review as FromPostgresRow>::COLUMNS, 6);
+
+    let row = row(&[
+        ("count", Type::INT8, &2i64),
+        ("id", Type::INT4, &10i32),
+        ("created_by", Type::TEXT, &"alice"),
+        ("updated_by", Type::TEXT, &"bob"),
+        ("id", Type::INT8, &1i64),
+        ("name", Type::TEXT, &"Ursula"),
+        ("score", Type::INT4, &4i32),
+    ]);
+    let expected = Review {
+        id: 10,
+        audit: Audit {
+            created_by: "alice".to_string(),
+            updated_by: "bob".to_string(),
+        },
+        author: Author {
+            id: 1,
+            name: "Ursula".to_string(),
+        },
+        cache: Cache::default(),
+        score: 4,
+    };
+    assert_eq!(Review::from_row_at(&row, 1).unwrap(), expected);
+    assert_eq!(<i64 as FromPostgresRow>::from_row_at(&row, 0).unwrap(), 2);
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct AuditedBook {
+    title: String,
+    #[sqlc(embed)]
+    audit: Audit,
+}
+
+#[test]
+fn test_embed_by_name() {
+    // embedded structs are read by position after the columns of the preceding fields,
+    // even when the other fields are read by name
+    let row = row(&[
+        ("title", Type::TEXT, &"Dune"),
+        ("created_by", Type::TEXT, &"alice"),
+        ("updated_by", Type::TEXT, &"bob"),
+    ]);
+    assert_eq!(
+        AuditedBook::from_row(&row).unwrap(),
+        AuditedBook {
+            title: "Dune".to_string(),
+            audit: Audit {
+                created_by: "alice".to_string(),
+                updated_by: "bob".to_string(),
+            },
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct TableAuthor {
+    id: i32,
+    name: i32,
+}
+
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct Publisher {
+    id: i32,
+    name: i32,
+    country: i32,
+}
+
+/// Row struct as sqlc-gen generates it for `sqlc.embed()` columns
+#[derive(Debug, PartialEq, FromPostgresRow)]
+struct GetBookRow {
+    #[sqlc(index = 0)]
+    id: i32,
+    #[sqlc(embed)]
+    author: TableAuthor,
+    #[sqlc(index = 3)]
+    pages: i32,
+    #[sqlc(embed)]
+    publisher: Publisher,
+    #[sqlc(index = 7)]
+    year: i32,
+}
+
+#[test]
+fn test_generated_embed_indexes() {
+    // the embedded tables repeat column names, so each value is only found by position
+    let row = row(&[
+        ("id", Type::INT4, &1i32),
+        ("id", Type::INT4, &2i32),
+        ("name", Type::INT4, &3i32),
+        ("pages", Type::INT4, &4i32),
+        ("id", Type::INT4, &5i32),
+        ("name", Type::INT4, &6i32),
+        ("country", Type::INT4, &7i32),
+        ("year", Type::INT4, &8i32),
+    ]);
+    let expected = GetBookRow {
+        id: 1,
+        author: TableAuthor { id: 2, name: 3 },
+        pages: 4,
+        publisher: Publisher {
+            id: 5,
+            name: 6,
+            country: 7,
+        },
+        year: 8,
+    };
+    assert_eq!(GetBookRow::from_row(&row).unwrap(), expected);
+    assert_eq!(<GetBookRow as FromPostgresRow>::COLUMNS, 8);
+}
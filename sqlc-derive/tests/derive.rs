@@ -0,0 +1,5 @@
+#[test]
+fn derive() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/fail/*.rs");
+}
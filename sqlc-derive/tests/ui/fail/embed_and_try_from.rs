@@ -0,0 +1,12 @@
+#[derive(sqlc_core::FromPostgresRow)]
+struct Audit {
+    created_by: String,
+}
+
+#[derive(sqlc_core::FromPostgresRow)]
+struct Author {
+    #[sqlc(embed, try_from = "Audit")]
+    audit: Audit,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[derive(sqlc_core::FromPostgresRow)]
+struct Audit {
+    created_by: String,
+}
+
+#[derive(sqlc_core::FromPostgresRow)]
+struct Author {
+    #[sqlc(flatten, rename = "audit")]
+    audit: Audit,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(sqlc_core::FromPostgresRow)]
+struct Author {
+    #[sqlc(index = 0, rename = "id")]
+    id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(sqlc_core::FromPostgresRow)]
+union Value {
+    int: i64,
+    float: f64,
+}
+
+fn main() {}
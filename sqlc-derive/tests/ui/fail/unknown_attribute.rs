@@ -0,0 +1,7 @@
+#[derive(sqlc_core::FromPostgresRow)]
+struct Author {
+    #[sqlc(column = "id")]
+    id: i64,
+}
+
+fn main() {}
@@ -1,12 +1,16 @@
-use options::Options;
+use crate::error::{Error, ErrorKind, Result};
+pub(crate) use options::Options;
+use prepared_queries::PreparedQueries;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
 use sqlc_sqlc_community_neoeinstein_prost::plugin;
+use std::convert::TryFrom;
 use std::str::FromStr;
 use syn::Ident;
 use type_const::TypeConst;
 use type_enum::TypeEnum;
-use type_query::{QueryCommand, QueryValue, TypeQuery};
+pub(crate) use type_override::TypeOverride;
+use type_query::{QueryCommand, QueryValue, TypeCatalog, TypeQuery};
 use type_struct::TypeStruct;
 
 pub(crate) use multi_line::*;
@@ -15,8 +19,10 @@ pub(crate) use pg_data_type::*;
 mod multi_line;
 mod options;
 mod pg_data_type;
+mod prepared_queries;
 mod type_const;
 mod type_enum;
+mod type_override;
 mod type_query;
 mod type_struct;
 
@@ -24,29 +30,98 @@ pub fn get_ident(value: &str) -> Ident {
     format_ident!("{}", value)
 }
 
+/// Module of `sqlc_core` with the `DBTX` trait and copy writer of the selected driver
+pub(crate) fn driver_module(use_async: bool) -> TokenStream {
+    if use_async {
+        quote!(sqlc_core::r#async)
+    } else {
+        quote!(sqlc_core::sync)
+    }
+}
+
+/// Type of the `client` argument of the generated functions, the async ones accepting
+/// `dyn DynDBTX` too
+pub(crate) fn client_type(use_async: bool) -> TokenStream {
+    let driver = driver_module(use_async);
+    if use_async {
+        quote!((impl #driver::DBTX + ?Sized))
+    } else {
+        quote!(mut impl #driver::DBTX)
+    }
+}
+
 fn build_query(
     query: &plugin::Query,
     schemas: &[plugin::Schema],
     default_schema: &str,
     structs: &[TypeStruct],
-    use_async: bool,
-) -> (TypeQuery, Vec<TypeStruct>) {
+    options: &Options,
+) -> Result<(TypeQuery, Vec<TypeStruct>)> {
     let mut associated_structs = vec![];
 
-    // Query parameter limit, get it from the options
-    let query_cmd = QueryCommand::from_str(&query.cmd).expect("invalid query annotation");
+    let query_cmd = QueryCommand::from_str(&query.cmd)
+        .map_err(|_| ErrorKind::InvalidCommand(query.cmd.clone()))?;
+    if query_cmd == QueryCommand::ExecLastId {
+        match query.columns.len() {
+            0 => {
+                return Err(ErrorKind::InvalidQuery(
+                    ":execlastid requires a RETURNING clause".to_string(),
+                )
+                .into())
+            }
+            1 => {}
+            _ => {
+                return Err(ErrorKind::InvalidQuery(
+                    ":execlastid requires a RETURNING clause with a single column".to_string(),
+                )
+                .into())
+            }
+        }
+    }
+
+    if query_cmd == QueryCommand::CopyFrom {
+        if query.insert_into_table.is_none() {
+            return Err(ErrorKind::InvalidQuery(
+                ":copyfrom requires an INSERT INTO statement".to_string(),
+            )
+            .into());
+        }
+
+        if query.params.is_empty() {
+            return Err(
+                ErrorKind::InvalidQuery(":copyfrom requires parameters".to_string()).into(),
+            );
+        }
+
+        if let Some(p) = query
+            .params
+            .iter()
+            .find(|p| p.column.as_ref().is_none_or(|c| c.name.is_empty()))
+        {
+            return Err(ErrorKind::InvalidQuery(format!(
+                ":copyfrom parameter ${} must map to a table column",
+                p.number
+            ))
+            .into());
+        }
+    }
+
     let is_batch = query_cmd.is_batch();
-    let qpl = 3;
-    let arg = QueryValue::from_query_params(
-        &query.params,
+    let overrides = options.type_overrides();
+    let catalog = TypeCatalog {
         schemas,
         default_schema,
+        overrides: &overrides,
+    };
+    let args = QueryValue::from_query_params(
+        &query.params,
+        &catalog,
         &query.name,
-        qpl,
-        is_batch,
-    );
+        options,
+        query_cmd.takes_arg_list(),
+    )?;
 
-    if let Some(ref query_arg) = arg {
+    for query_arg in &args {
         if let Some(ref type_struct) = query_arg.type_struct {
             associated_structs.push(type_struct.clone());
         }
@@ -54,13 +129,12 @@ fn build_query(
 
     let (ret, has_new_struct) = QueryValue::from_query_columns(
         &query.columns,
-        schemas,
-        default_schema,
+        &catalog,
         structs,
         &query_cmd,
         &query.name,
         is_batch,
-    );
+    )?;
 
     if has_new_struct {
         if let Some(ref query_ret) = ret {
@@ -70,10 +144,30 @@ fn build_query(
         }
     }
 
-    (
-        TypeQuery::new(&query.name, &query.cmd, arg, ret, use_async),
+    // Overridden columns have a Rust type of their own, the server infers their type instead
+    let param_types = query
+        .params
+        .iter()
+        .map(|p| match p.column {
+            Some(ref col) if TypeOverride::find(&overrides, col, default_schema).is_none() => {
+                pg_type_name(col)
+            }
+            _ => "UNKNOWN".to_string(),
+        })
+        .collect();
+
+    Ok((
+        TypeQuery::new(
+            &query.name,
+            &query.cmd,
+            &query.text,
+            args,
+            ret,
+            options.clone(),
+        )
+        .with_param_types(param_types),
         associated_structs,
-    )
+    ))
 }
 
 fn build_enums_from_schema(schema: &plugin::Schema, default_schema: &str) -> Vec<TypeEnum> {
@@ -84,12 +178,16 @@ fn build_enums_from_schema(schema: &plugin::Schema, default_schema: &str) -> Vec
         .collect::<Vec<_>>()
 }
 
-fn build_structs_from_schema(schema: &plugin::Schema, default_schema: &str) -> Vec<TypeStruct> {
+fn build_structs_from_schema(
+    schema: &plugin::Schema,
+    default_schema: &str,
+    overrides: &[TypeOverride],
+) -> Result<Vec<TypeStruct>> {
     schema
         .tables
         .iter()
-        .map(|table| TypeStruct::from_table(table, schema, default_schema))
-        .collect::<Vec<_>>()
+        .map(|table| TypeStruct::from_table(table, schema, default_schema, overrides))
+        .collect()
 }
 
 #[derive(Default)]
@@ -98,6 +196,7 @@ pub struct CodePartials {
     constants: Vec<TypeConst>,
     structs: Vec<TypeStruct>,
     queries: Vec<TypeQuery>,
+    prepared_queries: Option<PreparedQueries>,
 }
 
 impl CodePartials {
@@ -110,11 +209,29 @@ impl CodePartials {
     }
 }
 
-impl From<plugin::GenerateRequest> for CodePartials {
-    fn from(req: plugin::GenerateRequest) -> Self {
-        let options: Options = req.settings.expect("could not find sqlc config").into();
-        let catalog = req.catalog.as_ref().unwrap();
+impl TryFrom<plugin::GenerateRequest> for CodePartials {
+    type Error = Vec<Error>;
+
+    /// Builds all code partials, collecting every error instead of stopping at the first one
+    fn try_from(req: plugin::GenerateRequest) -> std::result::Result<Self, Self::Error> {
+        let mut errors = vec![];
+
+        // keep going with the default options so that query errors are reported as well
+        let options = req
+            .settings
+            .ok_or(ErrorKind::MissingRequestField("settings").into())
+            .and_then(Options::try_from)
+            .unwrap_or_else(|e| {
+                errors.push(e);
+                Options::default()
+            });
 
+        let Some(catalog) = req.catalog.as_ref() else {
+            errors.push(ErrorKind::MissingRequestField("catalog").into());
+            return Err(errors);
+        };
+
+        let overrides = options.type_overrides();
         let mut code_partials = CodePartials::default();
 
         for schema in &catalog.schemas {
@@ -126,9 +243,10 @@ impl From<plugin::GenerateRequest> for CodePartials {
                 .enums
                 .extend(build_enums_from_schema(schema, &catalog.default_schema));
 
-            code_partials
-                .structs
-                .extend(build_structs_from_schema(schema, &catalog.default_schema));
+            match build_structs_from_schema(schema, &catalog.default_schema, &overrides) {
+                Ok(structs) => code_partials.structs.extend(structs),
+                Err(e) => errors.push(e),
+            }
         }
 
         for query in &req.queries {
@@ -136,22 +254,48 @@ impl From<plugin::GenerateRequest> for CodePartials {
                 continue;
             }
 
-            code_partials.constants.push(query.into());
-
-            let (query, associated_structs) = build_query(
+            let (type_query, associated_structs) = match build_query(
                 &query,
                 &catalog.schemas,
                 &catalog.default_schema,
                 &code_partials.structs,
-                options.use_async,
-            );
-            code_partials.queries.push(query);
+                &options,
+            ) {
+                Ok(built) => built,
+                Err(e) => {
+                    errors.push(e.with_query(query));
+                    continue;
+                }
+            };
+
+            code_partials.constants.push(query.into());
+
+            if type_query.command() == QueryCommand::CopyFrom {
+                code_partials.constants.push(TypeConst::copy_from(query));
+            }
+
+            code_partials.queries.push(type_query);
             code_partials.structs.extend(associated_structs);
         }
 
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        for type_struct in &mut code_partials.structs {
+            type_struct.decode_by_index = options.decode_by_index;
+        }
+
         code_partials.sort_all();
 
-        code_partials
+        if options.prepared_queries {
+            code_partials.prepared_queries = Some(PreparedQueries::new(
+                &code_partials.queries,
+                options.use_async,
+            ));
+        }
+
+        Ok(code_partials)
     }
 }
 
@@ -169,6 +313,7 @@ impl ToTokens for CodePartials {
             structs,
             constants,
             queries,
+            prepared_queries,
         } = self;
 
         tokens.extend(quote! {
@@ -177,6 +322,165 @@ impl ToTokens for CodePartials {
             #(#enums)*
             #(#structs)*
             #(#queries)*
+            #prepared_queries
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_request(queries: Vec<plugin::Query>) -> plugin::GenerateRequest {
+        plugin::GenerateRequest {
+            settings: Some(plugin::Settings {
+                codegen: Some(plugin::Codegen::default()),
+                ..Default::default()
+            }),
+            catalog: Some(plugin::Catalog {
+                default_schema: "public".to_string(),
+                ..Default::default()
+            }),
+            queries,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect_errors() {
+        let query = |name: &str, cmd: &str| plugin::Query {
+            name: name.to_string(),
+            cmd: cmd.to_string(),
+            filename: "query.sql".to_string(),
+            ..Default::default()
+        };
+        let req = create_request(vec![
+            query("GetAuthor", ":two"),
+            query("DeleteAuthor", ":exec"),
+            query("CreateAuthor", ":execlastid"),
+        ]);
+
+        let errors = CodePartials::try_from(req).err().unwrap();
+        assert_eq!(
+            errors,
+            vec![
+                Error::new(ErrorKind::InvalidCommand(":two".to_string()))
+                    .with_query(&query("GetAuthor", ":two")),
+                Error::new(ErrorKind::InvalidQuery(
+                    ":execlastid requires a RETURNING clause".to_string()
+                ))
+                .with_query(&query("CreateAuthor", ":execlastid")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_catalog() {
+        let mut req = create_request(vec![]);
+        req.catalog = None;
+
+        let errors = CodePartials::try_from(req).err().unwrap();
+        assert_eq!(
+            errors,
+            vec![Error::new(ErrorKind::MissingRequestField("catalog"))]
+        );
+    }
+
+    #[test]
+    fn test_time_crate_chrono() {
+        let column = |name: &str, typ: &str| plugin::Column {
+            name: name.to_string(),
+            not_null: true,
+            r#type: Some(plugin::Identifier {
+                name: typ.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let schema = plugin::Schema {
+            name: "public".to_string(),
+            tables: vec![plugin::Table {
+                rel: Some(plugin::Identifier {
+                    name: "authors".to_string(),
+                    ..Default::default()
+                }),
+                columns: vec![
+                    column("born_on", "date"),
+                    column("created_at", "pg_catalog.timestamptz"),
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut options = Options::default();
+        options.time_crate = options::TimeCrate::Chrono;
+
+        let structs =
+            build_structs_from_schema(&schema, "public", &options.type_overrides()).unwrap();
+        let data_types = structs[0]
+            .fields
+            .iter()
+            .map(|field| field.data_type.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            data_types,
+            vec!["chrono::NaiveDate", "chrono::DateTime<chrono::Utc>"]
+        );
+    }
+
+    #[test]
+    fn test_param_types() {
+        let param = |number: i32, typ: &str, is_array: bool| plugin::Parameter {
+            number,
+            column: Some(plugin::Column {
+                name: format!("p{number}"),
+                not_null: true,
+                is_array,
+                r#type: Some(plugin::Identifier {
+                    name: typ.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        };
+        let query = plugin::Query {
+            name: "DeleteAuthors".to_string(),
+            cmd: ":exec".to_string(),
+            text: "DELETE FROM authors WHERE name = $1 OR id = ANY($2) OR genre = $3".to_string(),
+            params: vec![
+                param(1, "text", false),
+                param(2, "pg_catalog.int8", true),
+                param(3, "type_genre", false),
+            ],
+            ..Default::default()
+        };
+
+        let (type_query, _) = build_query(&query, &[], "public", &[], &Options::default()).unwrap();
+        let pretty = |tokens: TokenStream| {
+            prettyplease::unparse(&syn::parse_file(&tokens.to_string()).unwrap())
+        };
+        assert_eq!(
+            pretty(type_query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn delete_authors(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    arg: DeleteAuthorsParams<'_>,
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("DELETE_AUTHORS", ":exec");
+                    let stmt = client.prepare_typed(
+                        DELETE_AUTHORS,
+                        &[
+                            sqlc_core::Type::TEXT,
+                            sqlc_core::Type::INT8_ARRAY,
+                            sqlc_core::Type::UNKNOWN,
+                        ],
+                    ).map_err(|e| context.wrap(e))?;
+                    client.execute(&stmt, &[&arg.p_1, &arg.p_2, &arg.p_3])
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+    }
+}
@@ -1,39 +1,147 @@
-use core::panic;
+use crate::codegen::TypeOverride;
+use crate::error::{Error, ErrorKind};
 use serde::{Deserialize, Serialize};
 use sqlc_sqlc_community_neoeinstein_prost::plugin;
+use std::convert::TryFrom;
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+/// Crate used for `date`, `time`, `timestamp` and `timestamptz` columns
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TimeCrate {
+    #[default]
+    Time,
+    Chrono,
+}
+
+impl TimeCrate {
+    fn type_overrides(&self) -> Vec<TypeOverride> {
+        let types: &[(&str, &str)] = match self {
+            TimeCrate::Time => &[],
+            TimeCrate::Chrono => &[
+                ("date", "chrono::NaiveDate"),
+                ("time", "chrono::NaiveTime"),
+                ("timestamp", "chrono::NaiveDateTime"),
+                ("timestamptz", "chrono::DateTime<chrono::Utc>"),
+            ],
+        };
+
+        types
+            .iter()
+            .map(|(db_type, rust_type)| TypeOverride {
+                db_type: Some(db_type.to_string()),
+                rust_type: rust_type.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub(crate) struct Options {
     #[serde(default)]
     pub use_async: bool,
 
+    /// Queries also get a `<name>_pool` function taking a deadpool `Pool`, checking out a
+    /// connection and preparing statements with its statement cache. Streamed, batch and
+    /// copy queries, whose results hold on to the connection, only take a client.
     #[serde(default)]
-    use_deadpool: bool,
+    pub use_deadpool: bool,
+
+    /// `:one` queries return `Option<T>` instead of failing when no row is found
+    #[serde(default)]
+    pub optional_one: bool,
+
+    /// `:many` queries fetch rows lazily instead of buffering them all in memory
+    #[serde(default)]
+    pub stream_many: bool,
+
+    /// Queries with more parameters than this take a params struct, `0` always uses one
+    #[serde(default = "default_query_parameter_limit")]
+    pub query_parameter_limit: usize,
+
+    /// Rust types used instead of the built-in mapping for database types or columns
+    #[serde(default)]
+    pub overrides: Vec<TypeOverride>,
+
+    #[serde(default)]
+    pub time_crate: TimeCrate,
+
+    /// Generated row structs decode columns by position instead of by name
+    #[serde(default)]
+    pub decode_by_index: bool,
+
+    /// Parameters take `&str`, `&[u8]` and `&serde_json::Value` instead of owned values
+    #[serde(default)]
+    pub borrowed_params: bool,
+
+    /// Also generate a `Queries` struct running the queries with statements prepared once
+    #[serde(default)]
+    pub prepared_queries: bool,
+}
+
+fn default_query_parameter_limit() -> usize {
+    1
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            use_async: false,
+            use_deadpool: false,
+            optional_one: false,
+            stream_many: false,
+            query_parameter_limit: default_query_parameter_limit(),
+            overrides: vec![],
+            time_crate: TimeCrate::default(),
+            decode_by_index: false,
+            borrowed_params: false,
+            prepared_queries: false,
+        }
+    }
 }
 
-impl From<plugin::Settings> for Options {
-    fn from(settings: plugin::Settings) -> Self {
+impl Options {
+    /// User overrides followed by the ones implied by other options, so that
+    /// the user ones take precedence
+    pub(crate) fn type_overrides(&self) -> Vec<TypeOverride> {
+        let mut overrides = self.overrides.clone();
+        overrides.extend(self.time_crate.type_overrides());
+        overrides
+    }
+}
+
+impl TryFrom<plugin::Settings> for Options {
+    type Error = Error;
+
+    fn try_from(settings: plugin::Settings) -> Result<Self, Self::Error> {
         let codegen = settings
             .codegen
             .as_ref()
-            .expect("codegen settings not defined in sqlc config");
-        let options_str = match std::str::from_utf8(&codegen.options) {
-            Ok(v) => v,
-            Err(e) => panic!("Invalid UTF-8 sequence in codegen options: {}", e),
-        };
+            .ok_or(ErrorKind::MissingRequestField("codegen settings"))?;
+        let options_str = std::str::from_utf8(&codegen.options)
+            .map_err(|e| ErrorKind::InvalidOptions(format!("invalid UTF-8 sequence: {}", e)))?;
 
         let mut options = Options::default();
         if !options_str.is_empty() {
-            options = serde_json::from_str(options_str).expect(
-                format!(
-                    "could not deserialize codegen options (valid object: {:?})",
-                    serde_json::to_string(&Options::default())
-                        .expect("could not convert options to json string"),
-                )
-                .as_str(),
+            options = serde_json::from_str(options_str).map_err(|e| {
+                ErrorKind::InvalidOptions(format!(
+                    "{} (valid object: {})",
+                    e,
+                    serde_json::to_string(&Options::default()).unwrap_or_default(),
+                ))
+            })?;
+        }
+
+        if options.use_deadpool && !options.use_async {
+            return Err(
+                ErrorKind::InvalidOptions("use_deadpool requires use_async".to_string()).into(),
             );
         }
 
-        options
+        for type_override in &options.overrides {
+            type_override.validate()?;
+        }
+
+        Ok(options)
     }
 }
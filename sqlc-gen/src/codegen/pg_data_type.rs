@@ -1,5 +1,6 @@
-use crate::codegen::get_punct_from_char_tokens;
 use crate::codegen::type_enum::enum_name;
+use crate::codegen::{get_punct_from_char_tokens, TypeOverride};
+use crate::error::{ErrorKind, Result};
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use sqlc_sqlc_community_neoeinstein_prost::plugin;
@@ -33,17 +34,48 @@ impl PgDataType {
         col: &plugin::Column,
         schemas: &[plugin::Schema],
         default_schema: &str,
-    ) -> Self {
-        Self::from(
-            col.r#type.as_ref().unwrap().name.as_str(),
-            &schemas,
-            &default_schema,
-        )
+        overrides: &[TypeOverride],
+    ) -> Result<Self> {
+        if let Some(type_override) = TypeOverride::find(overrides, col, default_schema) {
+            return Ok(PgDataType(type_override.rust_type.clone()));
+        }
+
+        let col_type = col
+            .r#type
+            .as_ref()
+            .ok_or_else(|| ErrorKind::MissingColumnType(col.name.clone()))?;
+
+        Ok(Self::from(col_type.name.as_str(), schemas, default_schema))
+    }
+
+    /// Wraps the type in `Vec` for array columns and in `Option` for nullable ones
+    pub fn wrap_col(self, col: &plugin::Column) -> Self {
+        let mut data_type = self;
+        if col.is_array {
+            data_type = PgDataType(format!("Vec<{data_type}>"));
+        }
+        if !col.not_null {
+            data_type = PgDataType(format!("Option<{data_type}>"));
+        }
+        data_type
+    }
+
+    /// Reference to the type, for owned types that have a borrowed counterpart
+    pub fn borrowed(&self, lifetime: &str) -> Option<Self> {
+        let pointee = match self.0.as_str() {
+            "String" => "str",
+            "Vec<u8>" => "[u8]",
+            "serde_json::Value" => "serde_json::Value",
+            _ => return None,
+        };
+        Some(PgDataType(format!("&{lifetime}{pointee}")))
     }
 
     pub fn from(s: &str, schemas: &[plugin::Schema], default_schema: &str) -> Self {
         let other_ret_type: String;
         let pg_data_type_string = match s {
+            "char" | "pg_catalog.char" => "i8",
+
             "smallint" | "int2" | "pg_catalog.int2" | "smallserial" | "serial2"
             | "pg_catalog.serial2" => "i16",
 
@@ -56,7 +88,9 @@ impl PgDataType {
             "real" | "float4" | "pg_catalog.float4" => "f32",
             "float" | "double precision" | "float8" | "pg_catalog.float8" => "f64",
 
-            // "numeric" | "pg_catalog.numeric" | "money" => "",
+            "numeric" | "pg_catalog.numeric" | "decimal" => "rust_decimal::Decimal",
+            "money" | "pg_catalog.money" => "sqlc_core::Money",
+
             "boolean" | "bool" | "pg_catalog.bool" => "bool",
 
             "json" | "jsonb" => "serde_json::Value",
@@ -70,10 +104,12 @@ impl PgDataType {
             "pg_catalog.timestamp" => "time::PrimitiveDateTime",
             "pg_catalog.timestamptz" | "timestamptz" => "time::OffsetDateTime",
 
-            "interval" | "pg_catalog.interval" => "i64",
+            "interval" | "pg_catalog.interval" => "sqlc_core::PgInterval",
             "text" | "pg_catalog.varchar" | "pg_catalog.bpchar" | "string" | "citext" | "ltree"
             | "lquery" | "ltxtquery" => "String",
 
+            "oid" | "pg_catalog.oid" => "u32",
+
             "uuid" => "uuid::Uuid",
             "inet" => "cidr::IpInet",
             "cidr" => "cidr::IpCidr",
@@ -110,8 +146,238 @@ impl PgDataType {
     }
 }
 
+/// Name of the `postgres_types::Type` constant for the type of a column, `UNKNOWN` for
+/// types without one, e.g. enums, which the server then infers. The type names match the
+/// ones of `PgDataType::from`, so that the Rust type of a parameter accepts its `Type`
+pub fn pg_type_name(col: &plugin::Column) -> String {
+    let type_name = col.r#type.as_ref().map_or("", |t| t.name.as_str());
+    let pg_type = match type_name {
+        "char" | "pg_catalog.char" => "CHAR",
+
+        "smallint" | "int2" | "pg_catalog.int2" | "smallserial" | "serial2"
+        | "pg_catalog.serial2" => "INT2",
+
+        "integer" | "int" | "int4" | "pg_catalog.int4" | "serial" | "serial4"
+        | "pg_catalog.serial4" => "INT4",
+
+        "bigint" | "int8" | "pg_catalog.int8" | "bigserial" | "serial8" | "pg_catalog.serial8" => {
+            "INT8"
+        }
+
+        "real" | "float4" | "pg_catalog.float4" => "FLOAT4",
+        "float" | "double precision" | "float8" | "pg_catalog.float8" => "FLOAT8",
+
+        "numeric" | "pg_catalog.numeric" | "decimal" => "NUMERIC",
+        "money" | "pg_catalog.money" => "MONEY",
+
+        "boolean" | "bool" | "pg_catalog.bool" => "BOOL",
+
+        "json" => "JSON",
+        "jsonb" => "JSONB",
+
+        "bytea" | "blob" | "pg_catalog.bytea" => "BYTEA",
+
+        "date" => "DATE",
+
+        "pg_catalog.time" => "TIME",
+
+        "pg_catalog.timestamp" => "TIMESTAMP",
+        "pg_catalog.timestamptz" | "timestamptz" => "TIMESTAMPTZ",
+
+        "interval" | "pg_catalog.interval" => "INTERVAL",
+
+        "text" => "TEXT",
+        "pg_catalog.varchar" => "VARCHAR",
+        "pg_catalog.bpchar" => "BPCHAR",
+
+        "oid" | "pg_catalog.oid" => "OID",
+
+        "uuid" => "UUID",
+        "inet" => "INET",
+        "cidr" => "CIDR",
+        "macaddr" => "MACADDR",
+
+        "bit" | "pg_catalog.bit" => "BIT",
+        "varbit" | "pg_catalog.varbit" => "VARBIT",
+        "point" => "POINT",
+        "box" => "BOX",
+        "path" => "PATH",
+
+        _ => return "UNKNOWN".to_string(),
+    };
+
+    if col.is_array || col.is_sqlc_slice {
+        format!("{pg_type}_ARRAY")
+    } else {
+        pg_type.to_string()
+    }
+}
+
 impl fmt::Display for PgDataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(&self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Types accepted by the `ToSql` impl of each Rust type of `PgDataType::from`
+    const ACCEPTS: &[(&str, &[&str])] = &[
+        ("i8", &["CHAR"]),
+        ("i16", &["INT2"]),
+        ("i32", &["INT4"]),
+        ("i64", &["INT8"]),
+        ("f32", &["FLOAT4"]),
+        ("f64", &["FLOAT8"]),
+        ("rust_decimal::Decimal", &["NUMERIC"]),
+        ("sqlc_core::Money", &["MONEY"]),
+        ("bool", &["BOOL"]),
+        ("serde_json::Value", &["JSON", "JSONB"]),
+        ("Vec<u8>", &["BYTEA"]),
+        ("time::Date", &["DATE"]),
+        ("time::Time", &["TIME"]),
+        ("time::PrimitiveDateTime", &["TIMESTAMP"]),
+        ("time::OffsetDateTime", &["TIMESTAMPTZ"]),
+        ("sqlc_core::PgInterval", &["INTERVAL"]),
+        ("String", &["VARCHAR", "TEXT", "BPCHAR", "NAME", "UNKNOWN"]),
+        ("u32", &["OID"]),
+        ("uuid::Uuid", &["UUID"]),
+        ("cidr::IpInet", &["INET"]),
+        ("cidr::IpCidr", &["CIDR"]),
+        ("eui48::MacAddress", &["MACADDR"]),
+        ("bit_vec::BitVec", &["BIT", "VARBIT"]),
+        ("geo_types::Point<f64>", &["POINT"]),
+        ("geo_types::Rect<f64>", &["BOX"]),
+        ("geo_types::LineString<f64>", &["PATH"]),
+    ];
+
+    const TYPE_NAMES: &[&str] = &[
+        "smallint",
+        "int2",
+        "pg_catalog.int2",
+        "smallserial",
+        "serial2",
+        "pg_catalog.serial2",
+        "integer",
+        "int",
+        "int4",
+        "pg_catalog.int4",
+        "serial",
+        "serial4",
+        "pg_catalog.serial4",
+        "bigint",
+        "int8",
+        "pg_catalog.int8",
+        "bigserial",
+        "serial8",
+        "pg_catalog.serial8",
+        "real",
+        "float4",
+        "pg_catalog.float4",
+        "float",
+        "double precision",
+        "float8",
+        "pg_catalog.float8",
+        "numeric",
+        "pg_catalog.numeric",
+        "decimal",
+        "money",
+        "pg_catalog.money",
+        "boolean",
+        "bool",
+        "pg_catalog.bool",
+        "json",
+        "jsonb",
+        "bytea",
+        "blob",
+        "pg_catalog.bytea",
+        "date",
+        "pg_catalog.time",
+        "pg_catalog.timez",
+        "pg_catalog.timestamp",
+        "pg_catalog.timestamptz",
+        "timestamptz",
+        "interval",
+        "pg_catalog.interval",
+        "text",
+        "pg_catalog.varchar",
+        "pg_catalog.bpchar",
+        "char",
+        "pg_catalog.char",
+        "string",
+        "citext",
+        "ltree",
+        "lquery",
+        "ltxtquery",
+        "oid",
+        "pg_catalog.oid",
+        "uuid",
+        "inet",
+        "cidr",
+        "macaddr",
+        "macaddr8",
+        "hstore",
+        "bit",
+        "varbit",
+        "pg_catalog.bit",
+        "pg_catalog.varbit",
+        "point",
+        "box",
+        "path",
+        "timestamp",
+        "time",
+        "timetz",
+        "xml",
+    ];
+
+    fn create_column(typ: &str, is_array: bool) -> plugin::Column {
+        plugin::Column {
+            r#type: Some(plugin::Identifier {
+                name: typ.to_string(),
+                ..Default::default()
+            }),
+            is_array,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pg_type_name_accepted_by_data_type() {
+        for type_name in TYPE_NAMES {
+            let data_type = PgDataType::from(type_name, &[], "public");
+            let pg_type = pg_type_name(&create_column(type_name, false));
+            if pg_type == "UNKNOWN" {
+                continue;
+            }
+
+            let accepted = ACCEPTS
+                .iter()
+                .find(|(rust_type, _)| *rust_type == data_type.0)
+                .map_or(&[][..], |(_, types)| *types);
+            assert!(
+                accepted.contains(&pg_type.as_str()),
+                "{}: {} does not accept {}",
+                type_name,
+                data_type,
+                pg_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_pg_type_name() {
+        assert_eq!(pg_type_name(&create_column("char", false)), "CHAR");
+        assert_eq!(
+            pg_type_name(&create_column("pg_catalog.bpchar", false)),
+            "BPCHAR"
+        );
+        assert_eq!(
+            pg_type_name(&create_column("pg_catalog.int4", true)),
+            "INT4_ARRAY"
+        );
+        assert_eq!(pg_type_name(&create_column("timestamp", false)), "UNKNOWN");
+        assert_eq!(pg_type_name(&plugin::Column::default()), "UNKNOWN");
+    }
+}
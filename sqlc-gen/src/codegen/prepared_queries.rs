@@ -0,0 +1,251 @@
+use crate::codegen::{client_type, driver_module, get_ident, TypeQuery};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+/// `Queries` struct preparing every statement once and running queries with them
+#[derive(Default)]
+pub struct PreparedQueries {
+    queries: Vec<TypeQuery>,
+    use_async: bool,
+}
+
+impl PreparedQueries {
+    pub fn new(queries: &[TypeQuery], use_async: bool) -> Self {
+        Self {
+            queries: queries
+                .iter()
+                .filter(|query| query.is_preparable())
+                .map(TypeQuery::as_prepared)
+                .collect(),
+            use_async,
+        }
+    }
+
+    fn generate_code(&self) -> TokenStream {
+        if self.queries.is_empty() {
+            return quote! {};
+        }
+
+        let idents = self
+            .queries
+            .iter()
+            .map(|query| get_ident(&query.name()))
+            .collect::<Vec<_>>();
+        let prepare_calls = self
+            .queries
+            .iter()
+            .map(TypeQuery::prepare_call)
+            .collect::<Vec<_>>();
+        let contexts = self
+            .queries
+            .iter()
+            .map(TypeQuery::query_context)
+            .collect::<Vec<_>>();
+        let queries = &self.queries;
+        let driver = driver_module(self.use_async);
+        let client_type = client_type(self.use_async);
+
+        let prepare = if self.use_async {
+            quote! {
+                pub(crate) async fn prepare(client: &#client_type) -> sqlc_core::Result<Self> {
+                    Ok(Self {
+                        #(#idents: #prepare_calls.await.map_err(|e| #contexts.wrap(e))?),*
+                    })
+                }
+            }
+        } else {
+            quote! {
+                pub(crate) fn prepare(client: &#client_type) -> sqlc_core::Result<Self> {
+                    Ok(Self {
+                        #(#idents: #prepare_calls.map_err(|e| #contexts.wrap(e))?),*
+                    })
+                }
+            }
+        };
+
+        quote! {
+            /// Statements prepared on a connection, to be used only with clients and
+            /// transactions of that same connection
+            #[derive(Clone, Debug)]
+            pub(crate) struct Queries {
+                #(#idents: #driver::Statement),*
+            }
+
+            impl Queries {
+                #prepare
+
+                #(#queries)*
+            }
+        }
+    }
+}
+
+impl ToTokens for PreparedQueries {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.generate_code());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::{Options, PgDataType, QueryValue};
+
+    fn pretty(tokens: TokenStream) -> String {
+        prettyplease::unparse(&syn::parse_file(&tokens.to_string()).unwrap())
+    }
+
+    fn create_queries(use_async: bool) -> Vec<TypeQuery> {
+        let options = Options {
+            use_async,
+            ..Options::default()
+        };
+        let id = QueryValue::new("id", Some(PgDataType("i64".to_string())), None, false);
+        let name = QueryValue::new("", Some(PgDataType("String".to_string())), None, false);
+
+        vec![
+            TypeQuery::new(
+                "DeleteAuthor",
+                ":exec",
+                "DELETE FROM authors WHERE id = $1",
+                vec![id.clone()],
+                None,
+                options.clone(),
+            ),
+            TypeQuery::new(
+                "GetAuthorName",
+                ":one",
+                "SELECT name FROM authors WHERE id = $1",
+                vec![id.clone()],
+                Some(name),
+                options.clone(),
+            ),
+            TypeQuery::new(
+                "DeleteAuthors",
+                ":batchexec",
+                "DELETE FROM authors WHERE id = $1",
+                vec![QueryValue::new(
+                    "id",
+                    Some(PgDataType("i64".to_string())),
+                    None,
+                    true,
+                )],
+                None,
+                options,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_generate_code() {
+        let prepared = PreparedQueries::new(&create_queries(false), false);
+        assert_eq!(
+            pretty(prepared.to_token_stream()),
+            pretty(quote! {
+                /// Statements prepared on a connection, to be used only with clients and
+                /// transactions of that same connection
+                #[derive(Clone, Debug)]
+                pub(crate) struct Queries {
+                    delete_author: sqlc_core::sync::Statement,
+                    get_author_name: sqlc_core::sync::Statement
+                }
+
+                impl Queries {
+                    pub(crate) fn prepare(client: &mut impl sqlc_core::sync::DBTX) -> sqlc_core::Result<Self> {
+                        Ok(Self {
+                            delete_author: client
+                                .prepare(DELETE_AUTHOR)
+                                .map_err(|e| sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec").wrap(e))?,
+                            get_author_name: client
+                                .prepare(GET_AUTHOR_NAME)
+                                .map_err(|e| sqlc_core::QueryContext::new("GET_AUTHOR_NAME", ":one").wrap(e))?
+                        })
+                    }
+
+                    pub(crate) fn delete_author(
+                        &self,
+                        client: &mut impl sqlc_core::sync::DBTX,
+                        id: i64,
+                    ) -> sqlc_core::Result<()> {
+                        let context = sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec");
+                        client.execute(&self.delete_author, &[&id]).map_err(|e| context.wrap(e))?;
+                        Ok(())
+                    }
+
+                    pub(crate) fn get_author_name(
+                        &self,
+                        client: &mut impl sqlc_core::sync::DBTX,
+                        id: i64,
+                    ) -> sqlc_core::Result<String> {
+                        let context = sqlc_core::QueryContext::new("GET_AUTHOR_NAME", ":one");
+                        let row = client.query_opt(&self.get_author_name, &[&id])
+                            .map_err(|e| context.wrap(e))?;
+                        let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+                        sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_generate_code_async() {
+        let prepared = PreparedQueries::new(&create_queries(true), true);
+        assert_eq!(
+            pretty(prepared.to_token_stream()),
+            pretty(quote! {
+                /// Statements prepared on a connection, to be used only with clients and
+                /// transactions of that same connection
+                #[derive(Clone, Debug)]
+                pub(crate) struct Queries {
+                    delete_author: sqlc_core::r#async::Statement,
+                    get_author_name: sqlc_core::r#async::Statement
+                }
+
+                impl Queries {
+                    pub(crate) async fn prepare(client: &(impl sqlc_core::r#async::DBTX + ?Sized)) -> sqlc_core::Result<Self> {
+                        Ok(Self {
+                            delete_author: client
+                                .prepare(DELETE_AUTHOR)
+                                .await
+                                .map_err(|e| sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec").wrap(e))?,
+                            get_author_name: client
+                                .prepare(GET_AUTHOR_NAME)
+                                .await
+                                .map_err(|e| sqlc_core::QueryContext::new("GET_AUTHOR_NAME", ":one").wrap(e))?
+                        })
+                    }
+
+                    pub(crate) async fn delete_author(
+                        &self,
+                        client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                        id: i64,
+                    ) -> sqlc_core::Result<()> {
+                        let context = sqlc_core::QueryContext::new("DELETE_AUTHOR", ":exec");
+                        client.execute(&self.delete_author, &[&id])
+                            .await
+                            .map_err(|e| context.wrap(e))?;
+                        Ok(())
+                    }
+
+                    pub(crate) async fn get_author_name(
+                        &self,
+                        client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                        id: i64,
+                    ) -> sqlc_core::Result<String> {
+                        let context = sqlc_core::QueryContext::new("GET_AUTHOR_NAME", ":one");
+                        let row = client.query_opt(&self.get_author_name, &[&id])
+                            .await
+                            .map_err(|e| context.wrap(e))?;
+                        let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+                        sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+                    }
+                }
+            })
+        );
+
+        assert!(PreparedQueries::new(&[], false)
+            .to_token_stream()
+            .is_empty());
+    }
+}
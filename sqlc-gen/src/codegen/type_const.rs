@@ -3,6 +3,10 @@ use convert_case::{Case, Casing};
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 
+fn quote_ident(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct TypeConst {
     name: String,
@@ -17,6 +21,31 @@ impl TypeConst {
         }
     }
 
+    /// Creates the `COPY ... FROM STDIN` statement used by a `:copyfrom` query
+    pub(crate) fn copy_from(query: &crate::plugin::Query) -> Self {
+        let table = query
+            .insert_into_table
+            .as_ref()
+            .expect("copyfrom query must insert into a table");
+
+        let mut table_name = quote_ident(&table.name);
+        if !table.schema.is_empty() {
+            table_name = format!("{}.{table_name}", quote_ident(&table.schema));
+        }
+
+        let columns = query
+            .params
+            .iter()
+            .map(|p| quote_ident(&p.column.as_ref().expect("column not found").name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        TypeConst::new(
+            format!("{}Copy", query.name),
+            format!("COPY {table_name} ({columns}) FROM STDIN (FORMAT binary)"),
+        )
+    }
+
     pub fn name(&self) -> String {
         self.name.to_case(Case::ScreamingSnake)
     }
@@ -104,4 +133,41 @@ Second line
             .to_string()
         );
     }
+
+    #[test]
+    fn test_copy_from() {
+        let column = |name: &str| crate::plugin::Column {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let query = crate::plugin::Query {
+            name: "CreateAuthors".to_string(),
+            cmd: ":copyfrom".to_string(),
+            params: vec![
+                crate::plugin::Parameter {
+                    number: 1,
+                    column: Some(column("name")),
+                },
+                crate::plugin::Parameter {
+                    number: 2,
+                    column: Some(column("bio")),
+                },
+            ],
+            insert_into_table: Some(crate::plugin::Identifier {
+                catalog: "".to_string(),
+                schema: "public".to_string(),
+                name: "authors".to_string(),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            TypeConst::copy_from(&query),
+            TypeConst::new(
+                "CreateAuthorsCopy",
+                r#"COPY "public"."authors" ("name", "bio") FROM STDIN (FORMAT binary)"#
+            )
+        );
+        assert_eq!(TypeConst::copy_from(&query).name(), "CREATE_AUTHORS_COPY");
+    }
 }
@@ -73,7 +73,7 @@ impl TypeEnum {
             .collect::<Vec<_>>();
 
         quote! {
-            #[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql)]
+            #[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql, sqlc_core::FromPostgresRow)]
             #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
             #[cfg_attr(feature = "hash", derive(Eq, Hash))]
             #[postgres(name=#type_name)]
@@ -132,11 +132,16 @@ mod tests {
         assert_eq!(
             create_enum(None, None).generate_code().to_string(),
             quote! {
-                #[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql)]
+                #[derive(Clone, Debug, PartialEq, postgres_derive::ToSql, postgres_derive::FromSql, sqlc_core::FromPostgresRow)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                #[postgres(name="enum_name")]
                 pub enum EnumName {
                     #[postgres(name="val1")]
+                    #[cfg_attr(feature = "serde_support", serde(rename="val1"))]
                     Val1,
                     #[postgres(name="val2")]
+                    #[cfg_attr(feature = "serde_support", serde(rename="val2"))]
                     Val2
                 }
             }
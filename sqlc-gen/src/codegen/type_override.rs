@@ -0,0 +1,209 @@
+use crate::error::{ErrorKind, Result};
+use serde::{Deserialize, Serialize};
+use sqlc_sqlc_community_neoeinstein_prost::plugin;
+
+fn strip_pg_catalog(s: &str) -> &str {
+    s.strip_prefix("pg_catalog.").unwrap_or(s)
+}
+
+/// Replaces the Rust type generated for a database type or a single column
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub(crate) struct TypeOverride {
+    /// Database type name such as `timestamptz` or `public.my_enum`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_type: Option<String>,
+
+    /// Column as `table.column` or `schema.table.column`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+
+    /// Path of the Rust type used instead, e.g. `chrono::DateTime<chrono::Utc>`
+    pub rust_type: String,
+
+    /// Only match nullable (`true`) or non-null (`false`) columns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullable: Option<bool>,
+
+    /// Only match array (`true`) or non-array (`false`) columns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub array: Option<bool>,
+}
+
+impl TypeOverride {
+    pub(crate) fn validate(&self) -> Result<()> {
+        let key = match (&self.db_type, &self.column) {
+            (Some(db_type), None) => db_type,
+            (None, Some(column)) => {
+                if !(2..=3).contains(&column.split('.').count()) {
+                    return Err(ErrorKind::InvalidOptions(format!(
+                        "override column `{column}` must be `table.column` or `schema.table.column`"
+                    ))
+                    .into());
+                }
+                column
+            }
+            _ => {
+                return Err(ErrorKind::InvalidOptions(
+                    "override must set exactly one of `db_type` or `column`".to_string(),
+                )
+                .into())
+            }
+        };
+
+        if syn::parse_str::<syn::Type>(&self.rust_type).is_err() {
+            return Err(ErrorKind::InvalidOptions(format!(
+                "override for `{key}` has an invalid rust_type `{}`",
+                self.rust_type
+            ))
+            .into());
+        }
+
+        Ok(())
+    }
+
+    fn matches_column(&self, col: &plugin::Column, default_schema: &str) -> bool {
+        let Some(column) = &self.column else {
+            return false;
+        };
+        let Some(table) = &col.table else {
+            return false;
+        };
+
+        let table_schema = if table.schema.is_empty() {
+            default_schema
+        } else {
+            table.schema.as_str()
+        };
+
+        match column.split('.').collect::<Vec<_>>()[..] {
+            [t, c] => table_schema == default_schema && t == table.name && c == col.name,
+            [s, t, c] => s == table_schema && t == table.name && c == col.name,
+            _ => false,
+        }
+    }
+
+    fn matches_db_type(&self, col: &plugin::Column) -> bool {
+        let (Some(db_type), Some(col_type)) = (&self.db_type, &col.r#type) else {
+            return false;
+        };
+
+        let mut col_type_name = col_type.name.clone();
+        if !col_type.schema.is_empty() {
+            col_type_name = format!("{}.{col_type_name}", col_type.schema);
+        }
+
+        strip_pg_catalog(db_type) == strip_pg_catalog(&col_type_name)
+            && self
+                .nullable
+                .is_none_or(|nullable| nullable != col.not_null)
+            && self.array.is_none_or(|array| array == col.is_array)
+    }
+
+    /// Finds the override for a column, column overrides take precedence over `db_type` ones
+    pub(crate) fn find<'a>(
+        overrides: &'a [TypeOverride],
+        col: &plugin::Column,
+        default_schema: &str,
+    ) -> Option<&'a TypeOverride> {
+        overrides
+            .iter()
+            .find(|o| o.matches_column(col, default_schema))
+            .or_else(|| overrides.iter().find(|o| o.matches_db_type(col)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_column(table: &str, name: &str, typ: &str, not_null: bool) -> plugin::Column {
+        plugin::Column {
+            name: name.to_string(),
+            not_null,
+            table: Some(plugin::Identifier {
+                name: table.to_string(),
+                ..Default::default()
+            }),
+            r#type: Some(plugin::Identifier {
+                name: typ.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn create_override(
+        db_type: Option<&str>,
+        column: Option<&str>,
+        rust_type: &str,
+    ) -> TypeOverride {
+        TypeOverride {
+            db_type: db_type.map(str::to_string),
+            column: column.map(str::to_string),
+            rust_type: rust_type.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let db_type = create_override(Some("timestamptz"), None, "chrono::DateTime<chrono::Utc>");
+        let column = create_override(None, Some("authors.created_at"), "crate::CreatedAt");
+        let overrides = vec![db_type.clone(), column.clone()];
+
+        let created_at = create_column("authors", "created_at", "pg_catalog.timestamptz", true);
+        let updated_at = create_column("authors", "updated_at", "timestamptz", true);
+        let name = create_column("authors", "name", "text", true);
+
+        assert_eq!(
+            TypeOverride::find(&overrides, &created_at, "public"),
+            Some(&column)
+        );
+        assert_eq!(
+            TypeOverride::find(&overrides, &updated_at, "public"),
+            Some(&db_type)
+        );
+        assert_eq!(TypeOverride::find(&overrides, &name, "public"), None);
+    }
+
+    #[test]
+    fn test_find_qualified() {
+        let mut not_null = create_override(Some("text"), None, "crate::Name");
+        not_null.nullable = Some(false);
+        let column = create_override(None, Some("other.authors.bio"), "crate::Bio");
+        let overrides = vec![not_null.clone(), column];
+
+        let bio = create_column("authors", "bio", "text", false);
+        let name = create_column("authors", "name", "text", true);
+
+        assert_eq!(TypeOverride::find(&overrides, &bio, "public"), None);
+        assert_eq!(
+            TypeOverride::find(&overrides, &name, "public"),
+            Some(&not_null)
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(create_override(Some("uuid"), None, "uuid::Uuid")
+            .validate()
+            .is_ok());
+        assert!(create_override(None, Some("authors.id"), "crate::AuthorId")
+            .validate()
+            .is_ok());
+        assert!(create_override(None, None, "uuid::Uuid")
+            .validate()
+            .is_err());
+        assert!(
+            create_override(Some("uuid"), Some("authors.id"), "uuid::Uuid")
+                .validate()
+                .is_err()
+        );
+        assert!(create_override(None, Some("id"), "crate::AuthorId")
+            .validate()
+            .is_err());
+        assert!(create_override(Some("uuid"), None, "uuid::<")
+            .validate()
+            .is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::codegen::{get_ident, DataType, PgDataType, TypeStruct};
+use crate::codegen::{
+    client_type, driver_module, get_ident, DataType, Options, PgDataType, TypeOverride, TypeStruct,
+};
+use crate::error::{ErrorKind, Result};
 use check_keyword::CheckKeyword;
 use convert_case::{Case, Casing};
 use core::panic;
@@ -15,13 +18,71 @@ fn escape(s: &str) -> String {
     }
 }
 
-fn param_name(p: &crate::plugin::Parameter) -> String {
-    let column = p.column.as_ref().expect("column not found");
+/// Names of the arguments and locals of generated functions, which positional
+/// parameters are renamed around
+const RESERVED_NAMES: &[&str] = &["client", "context", "stmt", "sink", "writer", "item"];
 
+fn param_name(number: i32, column: &crate::plugin::Column) -> String {
     if !column.name.is_empty() {
         column.name.to_case(convert_case::Case::Snake)
     } else {
-        format!("dollar_{}", p.number)
+        format!("dollar_{}", number)
+    }
+}
+
+/// Derives the command name PostgreSQL reports in the command tag
+/// (`INSERT`, `UPDATE`, ...) from the query text
+fn command_tag(query_text: &str) -> String {
+    let query_text = query_text
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // collect only the top level words so that CTE bodies and quoted strings or
+    // identifiers are skipped
+    let mut depth = 0;
+    let mut quote = None;
+    let mut word = String::new();
+    let mut words = vec![];
+    for c in query_text.chars() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' || (c.is_ascii_digit() && !word.is_empty()) {
+            if depth == 0 {
+                word.push(c);
+            }
+            continue;
+        }
+
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word).to_uppercase());
+        }
+
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '\'' | '"' => quote = Some(c),
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        words.push(word.to_uppercase());
+    }
+
+    match words.first().map(String::as_str) {
+        Some("WITH") => words
+            .iter()
+            .find(|w| ["SELECT", "INSERT", "UPDATE", "DELETE", "MERGE"].contains(&w.as_str()))
+            .cloned()
+            .unwrap_or_default(),
+        Some(w) => w.to_string(),
+        None => String::new(),
     }
 }
 
@@ -45,6 +106,8 @@ pub enum QueryCommand {
     BatchMany,
     #[strum(serialize = ":batchone")]
     BatchOne,
+    #[strum(serialize = ":copyfrom")]
+    CopyFrom,
 }
 
 impl QueryCommand {
@@ -62,11 +125,23 @@ impl QueryCommand {
         }
     }
 
+    pub fn takes_arg_list(&self) -> bool {
+        self.is_batch() || *self == Self::CopyFrom
+    }
+
     pub fn is_one(&self) -> bool {
         *self == Self::One || *self == Self::BatchOne
     }
 }
 
+/// Schemas and type overrides that column and parameter types are resolved against
+#[derive(Clone, Copy)]
+pub(crate) struct TypeCatalog<'a> {
+    pub schemas: &'a [crate::plugin::Schema],
+    pub default_schema: &'a str,
+    pub overrides: &'a [TypeOverride],
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct QueryValue {
     name: String,
@@ -92,69 +167,126 @@ impl QueryValue {
 
     pub(crate) fn from_query_params(
         params: &[crate::plugin::Parameter],
-        schemas: &[crate::plugin::Schema],
-        default_schema: &str,
+        catalog: &TypeCatalog,
         query_name: &str,
-        qpl: usize,
+        options: &Options,
         is_batch: bool,
-    ) -> Option<Self> {
-        if params.len() == 1 && qpl != 0 {
-            let p = params.first().unwrap();
-            let col = p.column.as_ref().unwrap();
-            Some(Self::new(
-                escape(&param_name(p)),
-                Some(PgDataType::from_col(col, &schemas, &default_schema)),
-                None,
-                is_batch,
-            ))
-        } else if params.len() > 1 {
-            let type_struct = TypeStruct::from_params(query_name, params, schemas, default_schema);
-            Some(Self::new("arg", None, Some(type_struct.clone()), is_batch))
+    ) -> Result<Vec<Self>> {
+        let TypeCatalog {
+            schemas,
+            default_schema,
+            overrides,
+        } = *catalog;
+
+        // batch and copyfrom queries take a list of values, so only a single
+        // parameter can be passed without a params struct
+        let qpl = options.query_parameter_limit;
+        let qpl = if is_batch { qpl.min(1) } else { qpl };
+        let borrowed = options.borrowed_params;
+
+        if params.is_empty() {
+            Ok(vec![])
+        } else if params.len() <= qpl {
+            let mut names: Vec<String> = RESERVED_NAMES.iter().map(|n| n.to_string()).collect();
+            if options.use_deadpool {
+                names.push("pool".to_string());
+            }
+            params
+                .iter()
+                .map(|p| {
+                    let col = p
+                        .column
+                        .as_ref()
+                        .ok_or(ErrorKind::MissingParamColumn(p.number))?;
+                    let mut name = escape(&param_name(p.number, col));
+                    if names.contains(&name) {
+                        name = format!("{name}_{}", p.number);
+                    }
+                    names.push(name.clone());
+
+                    // arrays are taken as slices, except in lists of batch arguments
+                    let mut data_type =
+                        PgDataType::from_col(col, schemas, default_schema, overrides)?;
+                    if col.is_array || col.is_sqlc_slice {
+                        data_type = match is_batch {
+                            false => PgDataType(format!("&[{data_type}]")),
+                            true => PgDataType(format!("Vec<{data_type}>")),
+                        };
+                    } else if borrowed && !is_batch {
+                        data_type = data_type.borrowed("").unwrap_or(data_type);
+                    }
+
+                    Ok(Self::new(name, Some(data_type), None, is_batch))
+                })
+                .collect()
         } else {
-            None
+            let mut type_struct = TypeStruct::from_params(
+                query_name,
+                params,
+                schemas,
+                default_schema,
+                overrides,
+                !is_batch,
+            )?;
+            if borrowed && !is_batch {
+                type_struct.borrow_values();
+            }
+            Ok(vec![Self::new("arg", None, Some(type_struct), is_batch)])
         }
     }
 
     pub(crate) fn from_query_columns(
         columns: &[crate::plugin::Column],
-        schemas: &[crate::plugin::Schema],
-        default_schema: &str,
+        catalog: &TypeCatalog,
         structs: &[TypeStruct],
         query_cmd: &QueryCommand,
         query_name: &str,
         is_batch: bool,
-    ) -> (Option<Self>, bool) {
-        if columns.len() == 1 {
-            let col = columns.first().unwrap();
-            (
-                Some(Self::new(
-                    "",
-                    Some(PgDataType::from_col(col, &schemas, &default_schema)),
-                    None,
-                    is_batch,
-                )),
-                false,
-            )
+    ) -> Result<(Option<Self>, bool)> {
+        let TypeCatalog {
+            schemas,
+            default_schema,
+            overrides,
+        } = *catalog;
+
+        if let [col] = columns {
+            if let Some(gs) = TypeStruct::find_embedded(structs, col, default_schema)? {
+                return Ok((
+                    Some(QueryValue::new("", None, Some(gs.clone()), is_batch)),
+                    false,
+                ));
+            }
+
+            let data_type =
+                PgDataType::from_col(col, schemas, default_schema, overrides)?.wrap_col(col);
+            Ok((Some(Self::new("", Some(data_type), None, is_batch)), false))
         } else if query_cmd.has_return_value() {
             let found_struct = structs
                 .iter()
-                .find(|s| s.has_same_fields(&columns, schemas, default_schema));
+                .find(|s| s.has_same_fields(columns, schemas, default_schema, overrides));
 
             let mut new_struct = false;
             let gs = match found_struct {
                 None => {
                     new_struct = true;
-                    TypeStruct::from_columns(query_name, columns, schemas, default_schema)
+                    TypeStruct::from_columns(
+                        query_name,
+                        columns,
+                        schemas,
+                        default_schema,
+                        overrides,
+                        structs,
+                    )?
                 }
                 Some(gs) => gs.clone(),
             };
 
-            (
+            Ok((
                 Some(QueryValue::new("", None, Some(gs), is_batch)),
                 new_struct,
-            )
+            ))
         } else {
-            (None, false)
+            Ok((None, false))
         }
     }
 
@@ -233,84 +365,224 @@ impl ToTokens for QueryValue {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct TypeQuery {
     name: String,
     cmd: String,
-    arg: Option<QueryValue>,
+    text: String,
+    args: Vec<QueryValue>,
     ret: Option<QueryValue>,
-    use_async: bool,
+    options: Options,
+    /// Generated as a method of `Queries`, running the statement it prepared
+    prepared: bool,
+    /// Generated as the `<name>_pool` function, checking out a connection from a pool
+    pool: bool,
+    /// `postgres_types::Type` constants of the parameters, in order
+    param_types: Vec<String>,
 }
 
 impl TypeQuery {
     pub fn new<S: Into<String>>(
         name: S,
         cmd: S,
-        arg: Option<QueryValue>,
+        text: S,
+        args: Vec<QueryValue>,
         ret: Option<QueryValue>,
-        use_async: bool,
+        options: Options,
     ) -> Self {
         Self {
             name: name.into(),
             cmd: cmd.into(),
-            arg,
+            text: text.into(),
+            args,
             ret,
-            use_async,
+            options,
+            prepared: false,
+            pool: false,
+            param_types: vec![],
         }
     }
 
-    fn constant_name(&self) -> String {
+    /// Sets the parameter types the statement is prepared with
+    pub(crate) fn with_param_types(mut self, param_types: Vec<String>) -> Self {
+        self.param_types = param_types;
+        self
+    }
+
+    /// The query as a method of `Queries`
+    pub(crate) fn as_prepared(&self) -> Self {
+        Self {
+            prepared: true,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the query runs a single statement that `Queries` can prepare up front.
+    /// Batch and copy queries already prepare their statement once per call.
+    pub(crate) fn is_preparable(&self) -> bool {
+        !self.command().takes_arg_list()
+    }
+
+    /// The query as the `<name>_pool` function
+    fn as_pool(&self) -> Self {
+        Self {
+            pool: true,
+            ..self.clone()
+        }
+    }
+
+    /// Whether a `<name>_pool` function, checking out a connection from a deadpool pool,
+    /// is generated next to the one taking a client. Results that hold on to the
+    /// connection, like streamed rows, need the client from the caller.
+    fn has_pool_variant(&self) -> bool {
+        self.options.use_deadpool
+            && !self.prepared
+            && self.is_preparable()
+            && !(self.command() == QueryCommand::Many && self.options.stream_many)
+    }
+
+    pub(crate) fn constant_name(&self) -> String {
         self.name.to_case(Case::ScreamingSnake)
     }
 
+    /// Prepares the statement, passing the parameter types when they are known, with
+    /// the statement cache of the checked out connection in `<name>_pool` functions
+    pub(crate) fn prepare_call(&self) -> TokenStream {
+        let ident_const_name = get_ident(&self.constant_name());
+        let param_types = self.param_types.iter().map(|t| get_ident(t));
+        match (self.param_types.is_empty(), self.pool) {
+            (true, false) => quote!(client.prepare(#ident_const_name)),
+            (true, true) => quote!(client.prepare_cached(#ident_const_name)),
+            (false, false) => quote! {
+                client.prepare_typed(#ident_const_name, &[#(sqlc_core::Type::#param_types),*])
+            },
+            (false, true) => quote! {
+                client.prepare_typed_cached(#ident_const_name, &[#(sqlc_core::Type::#param_types),*])
+            },
+        }
+    }
+
+    /// Query constant name and command, attached to the errors of the generated function
+    pub(crate) fn query_context(&self) -> TokenStream {
+        let constant_name = self.constant_name();
+        let cmd = &self.cmd;
+        quote!(sqlc_core::QueryContext::new(#constant_name, #cmd))
+    }
+
+    /// Statement passed to the client, the prepared one for `Queries` methods and the
+    /// one prepared by the function when there are parameters or it uses a pool
+    fn statement(&self) -> TokenStream {
+        if self.prepared {
+            let ident_name = get_ident(&self.name());
+            quote!(&self.#ident_name)
+        } else if !self.param_types.is_empty() || self.pool {
+            quote!(&stmt)
+        } else {
+            let ident_const_name = get_ident(&self.constant_name());
+            quote!(#ident_const_name)
+        }
+    }
+
+    /// Lifetime of the client borrowed by the returned rows, named when other
+    /// references in the signature make `'_` ambiguous
+    fn client_lifetime(&self) -> Option<TokenStream> {
+        let has_references = self.args.iter().any(|arg| match &arg.typ {
+            Some(typ) => typ.0.starts_with('&'),
+            None => arg
+                .type_struct
+                .as_ref()
+                .is_some_and(TypeStruct::has_lifetime),
+        });
+
+        (self.prepared || has_references).then(|| quote!('c))
+    }
+
     pub fn name(&self) -> String {
         self.name.to_case(Case::Snake)
     }
 
-    fn command(&self) -> QueryCommand {
+    pub(crate) fn command(&self) -> QueryCommand {
         QueryCommand::from_str(&self.cmd).unwrap()
     }
 
     fn to_fn_input_signature(&self) -> TokenStream {
+        self.to_fn_input_signature_with_lifetime(None)
+    }
+
+    fn to_fn_input_signature_with_lifetime(&self, lifetime: Option<TokenStream>) -> TokenStream {
         let ident_name = get_ident(&self.name());
-        let arg = self.arg.clone().unwrap_or_default();
-        let client_mut = if self.use_async {
-            quote!()
+        let args = &self.args;
+        let client_type = client_type(self.options.use_async);
+        let receiver = if self.prepared {
+            quote!(&self,)
         } else {
-            quote!(mut)
+            quote!()
         };
 
-        quote!(fn #ident_name(client: &#client_mut impl sqlc_core::DBTX, #arg))
+        if self.pool {
+            let ident_name = get_ident(&format!("{}_pool", self.name()));
+            return quote! {
+                fn #ident_name(pool: &deadpool_postgres::Pool, #(#args),*)
+            };
+        }
+
+        match lifetime {
+            Some(lifetime) => quote! {
+                fn #ident_name<#lifetime>(
+                    #receiver
+                    client: &#lifetime #client_type,
+                    #(#args),*
+                )
+            },
+            None => quote! {
+                fn #ident_name(#receiver client: &#client_type, #(#args),*)
+            },
+        }
     }
 
     fn to_field_list(&self) -> TokenStream {
-        let arg = self.arg.clone().unwrap_or_default();
-        arg.generate_fields_list()
+        let fields_lists = self.args.iter().map(|arg| arg.generate_fields_list());
+        quote!(#(#fields_lists),*)
     }
 
     fn method_for_one(&self) -> QueryMethod {
         let client = quote!(client);
-        let ident_const_name = get_ident(&self.constant_name());
+        let statement = self.statement();
 
         let fields_list = self.to_field_list();
 
         let ret = self.ret.as_ref().unwrap();
 
         let sig_fn_input = self.to_fn_input_signature();
+        if self.options.optional_one {
+            let sig = quote! { #sig_fn_input -> sqlc_core::Result<Option<#ret>> };
+            let fetch_stmt = quote! {
+                let row = #client.query_opt(#statement, &[#fields_list])
+            };
+            let fn_body = quote! {
+                row.map(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                    .transpose()
+                    .map_err(|e| context.wrap(e))
+            };
+
+            return QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async);
+        }
+
         let sig = quote! { #sig_fn_input -> sqlc_core::Result<#ret> };
         let fetch_stmt = quote! {
-            let row = #client.query_one(#ident_const_name, &[#fields_list])
+            let row = #client.query_opt(#statement, &[#fields_list])
         };
         let fn_body = quote! {
-            Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+            let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+            sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
         };
 
-        QueryMethod::new(sig, fn_body, fetch_stmt, self.use_async)
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
     }
 
     fn method_for_many(&self) -> QueryMethod {
         let client = quote!(client);
-        let ident_const_name = get_ident(&self.constant_name());
+        let statement = self.statement();
 
         let fields_list = self.to_field_list();
 
@@ -323,39 +595,207 @@ impl TypeQuery {
             >
         };
         let fetch_stmt = quote! {
-            let rows = #client.query(#ident_const_name, &[#fields_list])
+            let rows = #client.query(#statement, &[#fields_list])
         };
         let fn_body = quote! {
-            let iter = rows
-                .into_iter()
-                .map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)?));
+            let iter = rows.into_iter().map(move |row| {
+                sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+            });
 
             Ok(iter)
         };
 
-        QueryMethod::new(sig, fn_body, fetch_stmt, self.use_async)
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
+    }
+
+    fn method_for_many_stream(&self) -> QueryMethod {
+        let client = quote!(client);
+        let statement = self.statement();
+
+        let fields_list = self.to_field_list();
+
+        let ret = self.ret.as_ref().unwrap();
+
+        let client_lifetime = self.client_lifetime().filter(|_| !self.options.use_async);
+        let sig_fn_input = self.to_fn_input_signature_with_lifetime(client_lifetime.clone());
+        let fetch_stmt = quote! {
+            let rows = #client.query_raw(#statement, &[#fields_list])
+        };
+        let (sig, fn_body) = if self.options.use_async {
+            let sig = quote! {
+                #sig_fn_input -> sqlc_core::Result<
+                    impl futures::Stream<Item = sqlc_core::Result<#ret>>
+                >
+            };
+            let fn_body = quote! {
+                use futures::StreamExt;
+                let stream = rows.map(move |row| {
+                    row.map_err(sqlc_core::Error::from)
+                        .and_then(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                        .map_err(|e| context.wrap(e))
+                });
+
+                Ok(stream)
+            };
+            (sig, fn_body)
+        } else {
+            let lifetime = client_lifetime.unwrap_or(quote!('_));
+            let sig = quote! {
+                #sig_fn_input -> sqlc_core::Result<
+                    impl std::iter::Iterator<Item = sqlc_core::Result<#ret>> + #lifetime
+                >
+            };
+            let fn_body = quote! {
+                let iter = rows.map(move |row| {
+                    row.map_err(sqlc_core::Error::from)
+                        .and_then(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                        .map_err(|e| context.wrap(e))
+                });
+
+                Ok(iter)
+            };
+            (sig, fn_body)
+        };
+
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
     }
 
     fn method_for_exec(&self) -> QueryMethod {
         let client = quote!(client);
-        let ident_const_name = get_ident(&self.constant_name());
+        let statement = self.statement();
 
         let fields_list = self.to_field_list();
 
         let sig_fn_input = self.to_fn_input_signature();
         let sig = quote! { #sig_fn_input -> sqlc_core::Result<()> };
         let fetch_stmt = quote! {
-            #client.execute(#ident_const_name, &[#fields_list])
+            #client.execute(#statement, &[#fields_list])
         };
         let fn_body = quote! {
             Ok(())
         };
 
-        QueryMethod::new(sig, fn_body, fetch_stmt, self.use_async)
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
     }
 
-    fn method_for_batch(&self) -> QueryMethod {
+    fn method_for_exec_rows(&self) -> QueryMethod {
+        let client = quote!(client);
+        let statement = self.statement();
+
+        let fields_list = self.to_field_list();
+
+        let sig_fn_input = self.to_fn_input_signature();
+        let sig = quote! { #sig_fn_input -> sqlc_core::Result<u64> };
+        let fetch_stmt = quote! {
+            let rows_affected = #client.execute(#statement, &[#fields_list])
+        };
+        let fn_body = quote! {
+            Ok(rows_affected)
+        };
+
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
+    }
+
+    fn method_for_exec_result(&self) -> QueryMethod {
         let client = quote!(client);
+        let statement = self.statement();
+        let command_tag = command_tag(&self.text);
+
+        let fields_list = self.to_field_list();
+
+        let sig_fn_input = self.to_fn_input_signature();
+        let sig = quote! { #sig_fn_input -> sqlc_core::Result<sqlc_core::ExecResult> };
+        let fetch_stmt = quote! {
+            let rows_affected = #client.execute(#statement, &[#fields_list])
+        };
+        let fn_body = quote! {
+            Ok(sqlc_core::ExecResult::new(#command_tag, rows_affected))
+        };
+
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
+    }
+
+    fn method_for_exec_last_id(&self) -> QueryMethod {
+        let client = quote!(client);
+        let statement = self.statement();
+
+        let fields_list = self.to_field_list();
+
+        let ret = self.ret.as_ref().unwrap();
+
+        let sig_fn_input = self.to_fn_input_signature();
+        let sig = quote! { #sig_fn_input -> sqlc_core::Result<#ret> };
+        let fetch_stmt = quote! {
+            let row = #client.query_opt(#statement, &[#fields_list])
+        };
+        let fn_body = quote! {
+            let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+            row.try_get(0).map_err(|e| context.wrap(e))
+        };
+
+        QueryMethod::new(sig, fn_body, fetch_stmt, self.options.use_async)
+    }
+
+    fn method_for_copy_from(&self) -> QueryMethod {
+        let client = quote!(client);
+        let ident_copy_const_name = get_ident(&format!("{}_COPY", self.constant_name()));
+        let ident_name = get_ident(&self.name());
+        let client_type = client_type(self.options.use_async);
+
+        let arg = self.args.first().cloned().unwrap_or_default();
+        let arg_name = get_ident(&arg.name);
+        let arg_list = get_ident(format!("{}_list", arg.name).as_str());
+        let arg_type = arg.get_type();
+
+        let driver = driver_module(self.options.use_async);
+        let fields_list = self.to_field_list();
+        let sig = quote! {
+            fn #ident_name<I>(client: &#client_type, #arg) -> sqlc_core::Result<u64>
+            where
+                I: IntoIterator,
+                I::Item: std::borrow::Borrow<#arg_type>,
+        };
+        let prepare_call = self.prepare_call();
+        let stmt = quote! {
+            let stmt = #prepare_call
+        };
+        let fn_body = if self.options.use_async {
+            quote! {
+                let sink = #client
+                    .copy_in(#ident_copy_const_name)
+                    .await
+                    .map_err(|e| context.wrap(e))?;
+                let mut writer = std::pin::pin!(#driver::BinaryCopyInWriter::new(sink, stmt.params()));
+                for item in #arg_list {
+                    use std::borrow::Borrow;
+                    let #arg_name = item.borrow();
+                    writer
+                        .as_mut()
+                        .write(&[#fields_list])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                }
+                writer.finish().await.map_err(|e| context.wrap(e))
+            }
+        } else {
+            quote! {
+                let sink = #client
+                    .copy_in(#ident_copy_const_name)
+                    .map_err(|e| context.wrap(e))?;
+                let mut writer = #driver::BinaryCopyInWriter::new(sink, stmt.params());
+                for item in #arg_list {
+                    use std::borrow::Borrow;
+                    let #arg_name = item.borrow();
+                    writer.write(&[#fields_list]).map_err(|e| context.wrap(e))?;
+                }
+                writer.finish().map_err(|e| context.wrap(e))
+            }
+        };
+
+        QueryMethod::new(sig, fn_body, stmt, self.options.use_async)
+    }
+
+    fn method_for_batch(&self) -> QueryMethod {
         let command = self.command();
         let fut_ret = if command.has_return_value() {
             let ret = self.ret.as_ref().unwrap();
@@ -374,15 +814,15 @@ impl TypeQuery {
             quote!(())
         };
 
-        let ident_const_name = get_ident(&self.constant_name());
         let ident_name = get_ident(&self.name());
 
-        let arg = self.arg.clone().unwrap_or_default();
+        let arg = self.args.first().cloned().unwrap_or_default();
         let arg_name_str = arg.name.clone();
         let arg_name = get_ident(&arg_name_str);
         let arg_list = get_ident(format!("{arg_name_str}_list").as_str());
         let arg_type = arg.get_type();
 
+        let driver = driver_module(self.options.use_async);
         let fields_list = self.to_field_list();
         let sig = quote! {
             fn #ident_name<'a, C, I>(client: &'a C, #arg) -> sqlc_core::Result<
@@ -393,35 +833,46 @@ impl TypeQuery {
                     > + 'a,
             >
             where
-                C: sqlc_core::DBTX,
+                C: #driver::DBTX + ?Sized,
                 I: IntoIterator + 'a,
                 I::Item: std::borrow::Borrow<#arg_type> + 'a,
         };
+        let prepare_call = self.prepare_call();
         let stmt = quote! {
-            let stmt = #client.prepare(#ident_const_name)
+            let stmt = #prepare_call
         };
         let fn_res = match command {
             QueryCommand::BatchExec => {
                 quote! {
-                    client.execute(&stmt, &[#fields_list]).await?;
+                    client
+                        .execute(&stmt, &[#fields_list])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
                     Ok(())
                 }
             }
             QueryCommand::BatchOne => {
                 quote! {
                     let row = client
-                        .query_one(
+                        .query_opt(
                             &stmt,
                             &[#fields_list],
                         )
-                        .await?;
-                    Ok(sqlc_core::FromPostgresRow::from_row(&row)?)
+                        .await
+                        .map_err(|e| context.wrap(e))?
+                        .ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+                    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
                 }
             }
             QueryCommand::BatchMany => {
                 quote! {
-                    let rows = client.query(&stmt, &[#fields_list]).await?;
-                    let result = rows.into_iter().map(|row| Ok(sqlc_core::FromPostgresRow::from_row(&row)));
+                    let rows = client
+                        .query(&stmt, &[#fields_list])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    let result = rows.into_iter().map(move |row| Ok(
+                        sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+                    ));
 
                     Ok(Box::pin(futures::stream::iter(result)))
                 }
@@ -439,7 +890,7 @@ impl TypeQuery {
             };
             Ok(futures::stream::iter(#arg_list.into_iter().map(fut)))
         };
-        QueryMethod::new(sig, fn_body, stmt, self.use_async)
+        QueryMethod::new(sig, fn_body, stmt, self.options.use_async)
     }
 }
 
@@ -447,11 +898,19 @@ impl ToTokens for TypeQuery {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let query_method: QueryMethod = self.into();
         tokens.extend(query_method.to_token_stream());
+
+        if self.has_pool_variant() {
+            let pool_method: QueryMethod = (&self.as_pool()).into();
+            tokens.extend(pool_method.to_token_stream());
+        }
     }
 }
 
 struct QueryMethod {
     sig: TokenStream,
+    context: TokenStream,
+    checkout_stmt: Option<TokenStream>,
+    prepare_stmt: Option<TokenStream>,
     fetch_stmt: TokenStream,
     fn_body: TokenStream,
     use_async: bool,
@@ -466,6 +925,9 @@ impl QueryMethod {
     ) -> Self {
         Self {
             sig,
+            context: TokenStream::new(),
+            checkout_stmt: None,
+            prepare_stmt: None,
             fetch_stmt,
             fn_body,
             use_async,
@@ -475,18 +937,33 @@ impl QueryMethod {
 
 impl From<&TypeQuery> for QueryMethod {
     fn from(query: &TypeQuery) -> Self {
-        let query_method = match query.command() {
+        let mut query_method = match query.command() {
             QueryCommand::One => query.method_for_one(),
+            QueryCommand::Many if query.options.stream_many => query.method_for_many_stream(),
             QueryCommand::Many => query.method_for_many(),
-            QueryCommand::Exec
-            | QueryCommand::ExecRows
-            | QueryCommand::ExecResult
-            | QueryCommand::ExecLastId => query.method_for_exec(),
+            QueryCommand::Exec => query.method_for_exec(),
+            QueryCommand::ExecRows => query.method_for_exec_rows(),
+            QueryCommand::ExecResult => query.method_for_exec_result(),
+            QueryCommand::ExecLastId => query.method_for_exec_last_id(),
             QueryCommand::BatchMany | QueryCommand::BatchOne | QueryCommand::BatchExec => {
                 query.method_for_batch()
-            } // _ => quote! {},
+            }
+            QueryCommand::CopyFrom => query.method_for_copy_from(),
         };
 
+        query_method.context = query.query_context();
+
+        if query.pool {
+            query_method.checkout_stmt = Some(quote!(let client = pool.get()));
+        }
+
+        // batch and copy methods prepare their statement themselves
+        if query.is_preparable() && !query.prepared && (!query.param_types.is_empty() || query.pool)
+        {
+            let prepare_call = query.prepare_call();
+            query_method.prepare_stmt = Some(quote!(let stmt = #prepare_call));
+        }
+
         query_method
     }
 }
@@ -496,18 +973,26 @@ impl ToTokens for QueryMethod {
         let fn_code;
         let sig = &self.sig;
         let fn_body = &self.fn_body;
+        let context = &self.context;
         let fetch_stmt = &self.fetch_stmt;
+        let checkout_stmt = self.checkout_stmt.iter();
+        let prepare_stmt = self.prepare_stmt.iter();
         if self.use_async {
             fn_code = quote! {
                 pub(crate) async #sig {
-                    #fetch_stmt.await?;
+                    let context = #context;
+                    #(#checkout_stmt.await.map_err(|e| context.wrap(e))?;)*
+                    #(#prepare_stmt.await.map_err(|e| context.wrap(e))?;)*
+                    #fetch_stmt.await.map_err(|e| context.wrap(e))?;
                     #fn_body
                 }
             }
         } else {
             fn_code = quote! {
                 pub(crate) #sig {
-                    #fetch_stmt?;
+                    let context = #context;
+                    #(#prepare_stmt.map_err(|e| context.wrap(e))?;)*
+                    #fetch_stmt.map_err(|e| context.wrap(e))?;
                     #fn_body
                 }
             }
@@ -516,3 +1001,871 @@ impl ToTokens for QueryMethod {
         tokens.extend(fn_code);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pretty(tokens: TokenStream) -> String {
+        prettyplease::unparse(&syn::parse_file(&tokens.to_string()).unwrap())
+    }
+
+    const CATALOG: TypeCatalog<'static> = TypeCatalog {
+        schemas: &[],
+        default_schema: "public",
+        overrides: &[],
+    };
+
+    fn create_options(use_async: bool) -> Options {
+        Options {
+            use_async,
+            ..Options::default()
+        }
+    }
+
+    fn qpl_options(query_parameter_limit: usize) -> Options {
+        Options {
+            query_parameter_limit,
+            ..Options::default()
+        }
+    }
+
+    fn create_query(cmd: &str, args: Vec<QueryValue>, use_async: bool) -> TypeQuery {
+        TypeQuery::new(
+            "UpdateAuthor".to_string(),
+            cmd.to_string(),
+            "UPDATE authors SET bio = NULL WHERE id = $1".to_string(),
+            args,
+            None,
+            create_options(use_async),
+        )
+    }
+
+    fn create_arg() -> Vec<QueryValue> {
+        vec![QueryValue::new(
+            "id",
+            Some(PgDataType("i64".to_string())),
+            None,
+            false,
+        )]
+    }
+
+    #[test]
+    fn test_method_for_exec() {
+        let query = create_query(":exec", create_arg(), false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&id]).map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_exec_rows() {
+        let query = create_query(":execrows", create_arg(), false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let rows_affected = client.execute(UPDATE_AUTHOR, &[&id])
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_exec_rows_async() {
+        let query = create_query(":execrows", create_arg(), true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn update_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let rows_affected = client.execute(UPDATE_AUTHOR, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+            })
+        );
+    }
+
+    fn deadpool_options() -> Options {
+        let mut options = create_options(true);
+        options.use_deadpool = true;
+        options
+    }
+
+    #[test]
+    fn test_method_for_exec_rows_deadpool() {
+        let mut query = create_query(":execrows", create_arg(), true);
+        query.options = deadpool_options();
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn update_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let rows_affected = client.execute(UPDATE_AUTHOR, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+
+                pub(crate) async fn update_author_pool(
+                    pool: &deadpool_postgres::Pool,
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+                    let stmt = client
+                        .prepare_cached(UPDATE_AUTHOR)
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    let rows_affected = client.execute(&stmt, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+            })
+        );
+
+        let query = query.with_param_types(vec!["INT8".to_string()]);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn update_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let stmt = client
+                        .prepare_typed(UPDATE_AUTHOR, &[sqlc_core::Type::INT8])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    let rows_affected = client.execute(&stmt, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+
+                pub(crate) async fn update_author_pool(
+                    pool: &deadpool_postgres::Pool,
+                    id: i64
+                ) -> sqlc_core::Result<u64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execrows");
+                    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+                    let stmt = client
+                        .prepare_typed_cached(UPDATE_AUTHOR, &[sqlc_core::Type::INT8])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    let rows_affected = client.execute(&stmt, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(rows_affected)
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reserved_pool_name() {
+        let from_params = |options: &Options| {
+            QueryValue::from_query_params(
+                &[create_param(1, "pool", "text")],
+                &CATALOG,
+                "UpdateAuthor",
+                options,
+                false,
+            )
+            .unwrap()
+        };
+        let mut options = deadpool_options();
+        options.query_parameter_limit = 1;
+        assert_eq!(from_params(&qpl_options(1))[0].name, "pool");
+
+        let mut query = create_query(":exec", from_params(&options), true);
+        query.options = options;
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn update_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    pool_1: String
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&pool_1])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+
+                pub(crate) async fn update_author_pool(
+                    pool: &deadpool_postgres::Pool,
+                    pool_1: String
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    let client = pool.get().await.map_err(|e| context.wrap(e))?;
+                    let stmt = client
+                        .prepare_cached(UPDATE_AUTHOR)
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    client.execute(&stmt, &[&pool_1])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_command_tag() {
+        assert_eq!(command_tag("delete from authors where id = $1"), "DELETE");
+        assert_eq!(
+            command_tag("\n  -- comment\nINSERT INTO authors (name) VALUES ($1)"),
+            "INSERT"
+        );
+        assert_eq!(
+            command_tag(
+                "WITH deleted AS (DELETE FROM books RETURNING id) UPDATE authors SET bio = NULL"
+            ),
+            "UPDATE"
+        );
+        assert_eq!(
+            command_tag(
+                "WITH \"t1(\" AS (SELECT ')' AS s) DELETE FROM authors WHERE name IN (SELECT s FROM \"t1(\")"
+            ),
+            "DELETE"
+        );
+        assert_eq!(command_tag(""), "");
+    }
+
+    #[test]
+    fn test_method_for_exec_result() {
+        let query = create_query(":execresult", create_arg(), true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn update_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    id: i64
+                ) -> sqlc_core::Result<sqlc_core::ExecResult> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execresult");
+                    let rows_affected = client.execute(UPDATE_AUTHOR, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(sqlc_core::ExecResult::new("UPDATE", rows_affected))
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_exec_last_id() {
+        let mut query = create_query(":execlastid", create_arg(), false);
+        query.ret = Some(QueryValue::new(
+            "",
+            Some(PgDataType("i64".to_string())),
+            None,
+            false,
+        ));
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64
+                ) -> sqlc_core::Result<i64> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":execlastid");
+                    let row = client.query_opt(UPDATE_AUTHOR, &[&id]).map_err(|e| context.wrap(e))?;
+                    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+                    row.try_get(0).map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    fn create_copy_from_query(use_async: bool) -> TypeQuery {
+        let arg = QueryValue::new("name", Some(PgDataType("String".to_string())), None, true);
+        TypeQuery::new(
+            "CreateAuthors".to_string(),
+            ":copyfrom".to_string(),
+            "INSERT INTO authors (name) VALUES ($1)".to_string(),
+            vec![arg],
+            None,
+            create_options(use_async),
+        )
+    }
+
+    #[test]
+    fn test_method_for_copy_from() {
+        let query = create_copy_from_query(false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn create_authors<I>(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    name_list: I
+                ) -> sqlc_core::Result<u64>
+                where
+                    I: IntoIterator,
+                    I::Item: std::borrow::Borrow<String>,
+                {
+                    let context = sqlc_core::QueryContext::new("CREATE_AUTHORS", ":copyfrom");
+                    let stmt = client.prepare(CREATE_AUTHORS).map_err(|e| context.wrap(e))?;
+                    let sink = client.copy_in(CREATE_AUTHORS_COPY).map_err(|e| context.wrap(e))?;
+                    let mut writer = sqlc_core::sync::BinaryCopyInWriter::new(sink, stmt.params());
+                    for item in name_list {
+                        use std::borrow::Borrow;
+                        let name = item.borrow();
+                        writer.write(&[&name]).map_err(|e| context.wrap(e))?;
+                    }
+                    writer.finish().map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_copy_from_async() {
+        let query = create_copy_from_query(true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn create_authors<I>(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    name_list: I
+                ) -> sqlc_core::Result<u64>
+                where
+                    I: IntoIterator,
+                    I::Item: std::borrow::Borrow<String>,
+                {
+                    let context = sqlc_core::QueryContext::new("CREATE_AUTHORS", ":copyfrom");
+                    let stmt = client.prepare(CREATE_AUTHORS).await.map_err(|e| context.wrap(e))?;
+                    let sink = client.copy_in(CREATE_AUTHORS_COPY)
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    let mut writer = std::pin::pin!(
+                        sqlc_core::r#async::BinaryCopyInWriter::new(sink, stmt.params())
+                    );
+                    for item in name_list {
+                        use std::borrow::Borrow;
+                        let name = item.borrow();
+                        writer.as_mut().write(&[&name]).await.map_err(|e| context.wrap(e))?;
+                    }
+                    writer.finish().await.map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    fn create_one_query(optional_one: bool, use_async: bool) -> TypeQuery {
+        let mut options = create_options(use_async);
+        options.optional_one = optional_one;
+
+        let type_struct = TypeStruct::new(
+            "Author",
+            None,
+            crate::codegen::type_struct::StructType::Default,
+            vec![],
+        );
+        let ret = QueryValue::new("", None, Some(type_struct), false);
+        TypeQuery::new(
+            "GetAuthor".to_string(),
+            ":one".to_string(),
+            "SELECT * FROM authors WHERE id = $1".to_string(),
+            create_arg(),
+            Some(ret),
+            options,
+        )
+    }
+
+    #[test]
+    fn test_method_for_one() {
+        let query = create_one_query(false, false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn get_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64
+                ) -> sqlc_core::Result<Author> {
+                    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+                    let row = client.query_opt(GET_AUTHOR, &[&id]).map_err(|e| context.wrap(e))?;
+                    let row = row.ok_or_else(|| context.wrap(sqlc_core::Error::NotFound))?;
+                    sqlc_core::FromPostgresRow::from_row(&row).map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_one_optional() {
+        let query = create_one_query(true, true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn get_author(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                    id: i64
+                ) -> sqlc_core::Result<Option<Author> > {
+                    let context = sqlc_core::QueryContext::new("GET_AUTHOR", ":one");
+                    let row = client.query_opt(GET_AUTHOR, &[&id])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    row.map(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                        .transpose()
+                        .map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    fn create_many_query(use_async: bool) -> TypeQuery {
+        let mut options = create_options(use_async);
+        options.stream_many = true;
+
+        let type_struct = TypeStruct::new(
+            "Author",
+            None,
+            crate::codegen::type_struct::StructType::Default,
+            vec![],
+        );
+        let ret = QueryValue::new("", None, Some(type_struct), false);
+        TypeQuery::new(
+            "ListAuthors".to_string(),
+            ":many".to_string(),
+            "SELECT * FROM authors".to_string(),
+            vec![],
+            Some(ret),
+            options,
+        )
+    }
+
+    #[test]
+    fn test_method_for_many_stream() {
+        let query = create_many_query(false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn list_authors(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                ) -> sqlc_core::Result<
+                    impl std::iter::Iterator<Item = sqlc_core::Result<Author> > + '_
+                > {
+                    let context = sqlc_core::QueryContext::new("LIST_AUTHORS", ":many");
+                    let rows = client.query_raw(LIST_AUTHORS, &[]).map_err(|e| context.wrap(e))?;
+                    let iter = rows
+                        .map(move |row| {
+                            row.map_err(sqlc_core::Error::from)
+                                .and_then(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                                .map_err(|e| context.wrap(e))
+                        });
+                    Ok(iter)
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_many_stream_async() {
+        let query = create_many_query(true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) async fn list_authors(
+                    client: &(impl sqlc_core::r#async::DBTX + ?Sized),
+                ) -> sqlc_core::Result<
+                    impl futures::Stream<Item = sqlc_core::Result<Author> >
+                > {
+                    let context = sqlc_core::QueryContext::new("LIST_AUTHORS", ":many");
+                    let rows = client.query_raw(LIST_AUTHORS, &[])
+                        .await
+                        .map_err(|e| context.wrap(e))?;
+                    use futures::StreamExt;
+                    let stream = rows
+                        .map(move |row| {
+                            row.map_err(sqlc_core::Error::from)
+                                .and_then(|row| sqlc_core::FromPostgresRow::from_row(&row))
+                                .map_err(|e| context.wrap(e))
+                        });
+                    Ok(stream)
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_method_for_many_stream_deadpool() {
+        let mut query = create_many_query(true);
+        query.options.use_deadpool = true;
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(create_many_query(true).to_token_stream())
+        );
+    }
+
+    fn create_param(number: i32, name: &str, typ: &str) -> crate::plugin::Parameter {
+        crate::plugin::Parameter {
+            number,
+            column: Some(crate::plugin::Column {
+                name: name.to_string(),
+                not_null: true,
+                r#type: Some(crate::plugin::Identifier {
+                    name: typ.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn create_params() -> Vec<crate::plugin::Parameter> {
+        vec![
+            create_param(1, "id", "bigserial"),
+            create_param(2, "name", "text"),
+        ]
+    }
+
+    #[test]
+    fn test_query_parameter_limit() {
+        let params = create_params();
+        let from_params = |qpl: usize, is_batch: bool| {
+            QueryValue::from_query_params(
+                &params,
+                &CATALOG,
+                "UpdateAuthor",
+                &qpl_options(qpl),
+                is_batch,
+            )
+            .unwrap()
+            .iter()
+            .map(|arg| (arg.name.clone(), arg.type_struct.is_some()))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(from_params(0, false), vec![("arg".to_string(), true)]);
+        assert_eq!(from_params(1, false), vec![("arg".to_string(), true)]);
+        assert_eq!(
+            from_params(2, false),
+            vec![("id".to_string(), false), ("name".to_string(), false)]
+        );
+        assert_eq!(from_params(2, true), vec![("arg".to_string(), true)]);
+
+        assert!(QueryValue::from_query_params(
+            &[],
+            &CATALOG,
+            "UpdateAuthor",
+            &Options::default(),
+            false
+        )
+        .unwrap()
+        .is_empty());
+        let single = QueryValue::from_query_params(
+            &params[..1],
+            &CATALOG,
+            "UpdateAuthor",
+            &qpl_options(0),
+            false,
+        )
+        .unwrap();
+        assert_eq!(single.len(), 1);
+        assert!(single[0].type_struct.is_some());
+    }
+
+    #[test]
+    fn test_method_for_positional_params() {
+        let args = vec![
+            QueryValue::new("id", Some(PgDataType("i64".to_string())), None, false),
+            QueryValue::new("name", Some(PgDataType("String".to_string())), None, false),
+        ];
+        let query = create_query(":exec", args, false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64,
+                    name: String,
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&id, &name]).map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reserved_param_names() {
+        let params = vec![
+            create_param(1, "context", "text"),
+            create_param(2, "client", "text"),
+        ];
+        let args = QueryValue::from_query_params(
+            &params,
+            &CATALOG,
+            "UpdateAuthor",
+            &qpl_options(2),
+            false,
+        )
+        .unwrap();
+        let query = create_query(":exec", args, false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    context_1: String,
+                    client_2: String,
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&context_1, &client_2])
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_reserved_local_names() {
+        let from_params = |name: &str, is_batch: bool| {
+            QueryValue::from_query_params(
+                &[create_param(1, name, "text")],
+                &CATALOG,
+                "CreateAuthors",
+                &qpl_options(1),
+                is_batch,
+            )
+            .unwrap()
+        };
+
+        let query = create_query(":exec", from_params("stmt", false), false)
+            .with_param_types(vec!["TEXT".to_string()]);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    stmt_1: String,
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    let stmt = client
+                        .prepare_typed(UPDATE_AUTHOR, &[sqlc_core::Type::TEXT])
+                        .map_err(|e| context.wrap(e))?;
+                    client.execute(&stmt, &[&stmt_1]).map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+
+        let mut query = create_copy_from_query(false);
+        query.args = from_params("writer", true);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn create_authors<I>(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    writer_1_list: I
+                ) -> sqlc_core::Result<u64>
+                where
+                    I: IntoIterator,
+                    I::Item: std::borrow::Borrow<String>,
+                {
+                    let context = sqlc_core::QueryContext::new("CREATE_AUTHORS", ":copyfrom");
+                    let stmt = client.prepare(CREATE_AUTHORS).map_err(|e| context.wrap(e))?;
+                    let sink = client.copy_in(CREATE_AUTHORS_COPY).map_err(|e| context.wrap(e))?;
+                    let mut writer = sqlc_core::sync::BinaryCopyInWriter::new(sink, stmt.params());
+                    for item in writer_1_list {
+                        use std::borrow::Borrow;
+                        let writer_1 = item.borrow();
+                        writer.write(&[&writer_1]).map_err(|e| context.wrap(e))?;
+                    }
+                    writer.finish().map_err(|e| context.wrap(e))
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_scalar_return_type() {
+        let column = |typ: &str, not_null: bool, is_array: bool| crate::plugin::Column {
+            name: "bio".to_string(),
+            not_null,
+            is_array,
+            r#type: Some(crate::plugin::Identifier {
+                name: typ.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let return_type = |col: crate::plugin::Column| {
+            let (ret, _) = QueryValue::from_query_columns(
+                &[col],
+                &CATALOG,
+                &[],
+                &QueryCommand::One,
+                "GetAuthorBio",
+                false,
+            )
+            .unwrap();
+            ret.unwrap().typ.unwrap().to_string()
+        };
+
+        assert_eq!(return_type(column("text", true, false)), "String");
+        assert_eq!(return_type(column("text", false, false)), "Option<String>");
+        assert_eq!(return_type(column("int4", true, true)), "Vec<i32>");
+        assert_eq!(
+            return_type(column("text", false, true)),
+            "Option<Vec<String>>"
+        );
+    }
+
+    #[test]
+    fn test_array_params() {
+        let mut params = create_params();
+        if let Some(col) = params[1].column.as_mut() {
+            col.name = "ids".to_string();
+            col.is_array = true;
+            col.r#type = Some(crate::plugin::Identifier {
+                name: "int8".to_string(),
+                ..Default::default()
+            });
+        }
+        let from_params = |qpl: usize, is_batch: bool| {
+            QueryValue::from_query_params(
+                &params,
+                &CATALOG,
+                "UpdateAuthors",
+                &qpl_options(qpl),
+                is_batch,
+            )
+            .unwrap()
+        };
+
+        let query = create_query(":exec", from_params(2, false), false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64,
+                    ids: &[i64],
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&id, &ids]).map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+
+        let args = from_params(1, false);
+        let type_struct = args[0].type_struct.as_ref().unwrap();
+        assert_eq!(
+            pretty(type_struct.to_token_stream()),
+            pretty(quote! {
+                #[derive(Clone, Debug, PartialEq)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                pub(crate) struct UpdateAuthorsParams<'a> {
+                    pub id: i64,
+                    pub ids: &'a [i64]
+                }
+            })
+        );
+        let query = create_query(":exec", args, false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    arg: UpdateAuthorsParams<'_>,
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&arg.id, &arg.ids])
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+
+        let batch = from_params(1, true);
+        assert!(!batch[0].type_struct.as_ref().unwrap().has_lifetime());
+    }
+
+    #[test]
+    fn test_borrowed_params() {
+        let mut params = create_params();
+        params.push(crate::plugin::Parameter {
+            number: 3,
+            column: Some(crate::plugin::Column {
+                name: "avatar".to_string(),
+                r#type: Some(crate::plugin::Identifier {
+                    name: "bytea".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        });
+        let from_params = |qpl: usize| {
+            let mut options = qpl_options(qpl);
+            options.borrowed_params = true;
+            QueryValue::from_query_params(&params, &CATALOG, "UpdateAuthor", &options, false)
+                .unwrap()
+        };
+
+        let query = create_query(":exec", from_params(3), false);
+        assert_eq!(
+            pretty(query.to_token_stream()),
+            pretty(quote! {
+                pub(crate) fn update_author(
+                    client: &mut impl sqlc_core::sync::DBTX,
+                    id: i64,
+                    name: &str,
+                    avatar: &[u8],
+                ) -> sqlc_core::Result<()> {
+                    let context = sqlc_core::QueryContext::new("UPDATE_AUTHOR", ":exec");
+                    client.execute(UPDATE_AUTHOR, &[&id, &name, &avatar])
+                        .map_err(|e| context.wrap(e))?;
+                    Ok(())
+                }
+            })
+        );
+
+        let args = from_params(1);
+        assert_eq!(
+            pretty(args[0].type_struct.as_ref().unwrap().to_token_stream()),
+            pretty(quote! {
+                #[derive(Clone, Debug, PartialEq)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                pub(crate) struct UpdateAuthorParams<'a> {
+                    pub id: i64,
+                    pub name: &'a str,
+                    pub avatar: Option<&'a [u8]>
+                }
+            })
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use crate::codegen::{get_ident, plugin, DataType, PgDataType};
+use crate::codegen::{get_ident, plugin, DataType, PgDataType, TypeOverride};
+use crate::error::{ErrorKind, Result};
 use convert_case::{Case, Casing};
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{quote, ToTokens};
 
 fn column_name(name: &str, pos: i32) -> String {
@@ -38,6 +39,10 @@ pub struct StructField {
     pub not_null: bool,
     pub number: i32,
     pub data_type: PgDataType,
+    /// Column count of the table struct embedded with `sqlc.embed()`
+    pub embed: Option<usize>,
+    /// Parameter field that may borrow its value, arrays become `&'a [T]`
+    pub borrowed: bool,
 }
 
 impl StructField {
@@ -54,6 +59,22 @@ impl StructField {
             data_type,
             is_array,
             not_null,
+            embed: None,
+            borrowed: false,
+        }
+    }
+
+    /// Field holding a table struct embedded with `sqlc.embed()`
+    pub fn embedded(type_struct: &TypeStruct, pos: i32) -> Self {
+        Self {
+            embed: Some(type_struct.fields.len()),
+            ..Self::new(
+                column_name(&type_struct.name(), pos),
+                pos,
+                PgDataType(type_struct.name()),
+                false,
+                true,
+            )
         }
     }
 
@@ -62,14 +83,15 @@ impl StructField {
         pos: i32,
         schemas: &[plugin::Schema],
         default_schema: &str,
-    ) -> Self {
-        Self::new(
+        overrides: &[TypeOverride],
+    ) -> Result<Self> {
+        Ok(Self::new(
             &col.name,
             pos,
-            PgDataType::from_col(col, &schemas, default_schema),
+            PgDataType::from_col(col, schemas, default_schema, overrides)?,
             col.is_array,
             col.not_null,
-        )
+        ))
     }
 
     fn matches_column(
@@ -78,12 +100,17 @@ impl StructField {
         field_table: Option<&plugin::Identifier>,
         schemas: &[plugin::Schema],
         default_schema: &str,
+        overrides: &[TypeOverride],
         pos: i32,
     ) -> bool {
+        if self.embed.is_some() || col.embed_table.is_some() {
+            return false;
+        }
+
         let same_name = self.name() == column_name(&col.name, pos);
 
-        let same_type = self.data_type.to_string()
-            == PgDataType::from_col(col, schemas, default_schema).to_string();
+        let same_type = PgDataType::from_col(col, schemas, default_schema, overrides)
+            .is_ok_and(|data_type| self.data_type == data_type);
 
         let same_table = same_table(col.table.as_ref(), field_table, default_schema);
 
@@ -94,11 +121,18 @@ impl StructField {
         column_name(&self.name, self.number)
     }
 
+    fn is_borrowed(&self) -> bool {
+        self.borrowed && (self.is_array || self.data_type.0.starts_with('&'))
+    }
+
     fn data_type(&self) -> TokenStream {
         let mut tokens = self.data_type.to_token_stream();
 
         if self.is_array {
-            tokens = quote!(Vec<#tokens>);
+            tokens = match self.borrowed {
+                true => quote!(&'a [#tokens]),
+                false => quote!(Vec<#tokens>),
+            };
         }
 
         if !self.not_null {
@@ -119,6 +153,9 @@ impl ToTokens for StructField {
         let field_name_ident = get_ident(&self.name());
         let field_type_ident = self.data_type();
 
+        if self.embed.is_some() {
+            tokens.extend(quote! { #[sqlc(embed)] });
+        }
         tokens.extend(quote! {
             pub #field_name_ident: #field_type_ident
         })
@@ -139,6 +176,7 @@ pub struct TypeStruct {
     pub table: Option<plugin::Identifier>,
     struct_type: StructType,
     pub fields: Vec<StructField>,
+    pub decode_by_index: bool,
 }
 
 impl TypeStruct {
@@ -153,6 +191,7 @@ impl TypeStruct {
             table,
             struct_type,
             fields,
+            decode_by_index: false,
         }
     }
 
@@ -160,39 +199,53 @@ impl TypeStruct {
         columns: &[plugin::Column],
         schemas: &[plugin::Schema],
         default_schema: &str,
-    ) -> Vec<StructField> {
+        overrides: &[TypeOverride],
+    ) -> Result<Vec<StructField>> {
         columns
             .iter()
             .enumerate()
-            .map(|(i, col)| StructField::from(col, i as i32, schemas, default_schema))
-            .collect::<Vec<_>>()
+            .map(|(i, col)| StructField::from(col, i as i32, schemas, default_schema, overrides))
+            .collect()
     }
 
     pub fn from_table(
         table: &crate::plugin::Table,
         schema: &plugin::Schema,
         default_schema: &str,
-    ) -> Self {
-        let table_rel = table.rel.as_ref().unwrap();
+        overrides: &[TypeOverride],
+    ) -> Result<Self> {
+        let table_rel = table.rel.as_ref().ok_or(ErrorKind::MissingTableName)?;
         let mut table_name = table_rel.name.clone();
         if schema.name != default_schema {
             table_name = format!("{}_{table_name}", schema.name);
         }
 
+        let table_id = plugin::Identifier {
+            catalog: "".to_string(),
+            schema: schema.name.clone(),
+            name: table_rel.name.clone(),
+        };
+
+        // column overrides are matched by table, which catalog columns may not carry
+        let columns = table
+            .columns
+            .iter()
+            .map(|col| plugin::Column {
+                table: col.table.clone().or_else(|| Some(table_id.clone())),
+                ..col.clone()
+            })
+            .collect::<Vec<_>>();
+
         let struct_name = pluralizer::pluralize(table_name.as_str(), 1, false);
         let fields =
-            Self::column_to_struct_fields(&table.columns, &[schema.clone()], default_schema);
+            Self::column_to_struct_fields(&columns, &[schema.clone()], default_schema, overrides)?;
 
-        Self::new(
+        Ok(Self::new(
             struct_name,
-            Some(plugin::Identifier {
-                catalog: "".to_string(),
-                schema: schema.name.clone(),
-                name: table_rel.name.clone(),
-            }),
+            Some(table_id),
             StructType::Default,
             fields,
-        )
+        ))
     }
 
     pub fn from_columns(
@@ -200,31 +253,65 @@ impl TypeStruct {
         columns: &[plugin::Column],
         schemas: &[plugin::Schema],
         default_schema: &str,
-    ) -> Self {
-        let fields = Self::column_to_struct_fields(columns, schemas, default_schema);
-
-        Self::new(struct_name, None, StructType::Row, fields)
+        overrides: &[TypeOverride],
+        structs: &[TypeStruct],
+    ) -> Result<Self> {
+        let fields = columns
+            .iter()
+            .enumerate()
+            .map(
+                |(i, col)| match Self::find_embedded(structs, col, default_schema)? {
+                    Some(type_struct) => Ok(StructField::embedded(type_struct, i as i32)),
+                    None => StructField::from(col, i as i32, schemas, default_schema, overrides),
+                },
+            )
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(struct_name, None, StructType::Row, fields))
     }
 
+    /// Builds the params struct of a query, `borrowed` fields take arrays as slices
     pub fn from_params(
         struct_name: &str,
         params: &[plugin::Parameter],
         schemas: &[plugin::Schema],
         default_schema: &str,
-    ) -> Self {
+        overrides: &[TypeOverride],
+        borrowed: bool,
+    ) -> Result<Self> {
         let fields = params
             .iter()
             .map(|field| {
-                StructField::from(
-                    &field.column.as_ref().unwrap(),
-                    field.number,
-                    &schemas,
-                    &default_schema,
-                )
+                let column = field
+                    .column
+                    .as_ref()
+                    .ok_or(ErrorKind::MissingParamColumn(field.number))?;
+                let mut field =
+                    StructField::from(column, field.number, schemas, default_schema, overrides)?;
+                field.is_array |= column.is_sqlc_slice;
+                field.borrowed = borrowed;
+                Ok(field)
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(struct_name, None, StructType::Params, fields))
+    }
 
-        Self::new(struct_name, None, StructType::Params, fields)
+    /// Finds the table struct of a column selected with `sqlc.embed()`
+    pub fn find_embedded<'a>(
+        structs: &'a [TypeStruct],
+        col: &plugin::Column,
+        default_schema: &str,
+    ) -> Result<Option<&'a TypeStruct>> {
+        let Some(embed_table) = &col.embed_table else {
+            return Ok(None);
+        };
+
+        structs
+            .iter()
+            .find(|s| same_table(Some(embed_table), s.table.as_ref(), default_schema))
+            .map(Some)
+            .ok_or_else(|| ErrorKind::MissingEmbedTable(embed_table.name.clone()).into())
     }
 
     pub fn has_same_fields(
@@ -232,6 +319,7 @@ impl TypeStruct {
         columns: &[plugin::Column],
         schemas: &[plugin::Schema],
         default_schema: &str,
+        overrides: &[TypeOverride],
     ) -> bool {
         if self.fields.len() != columns.len() {
             false
@@ -246,6 +334,7 @@ impl TypeStruct {
                         self.table.as_ref(),
                         schemas,
                         default_schema,
+                        overrides,
                         i as i32,
                     )
                 })
@@ -262,8 +351,30 @@ impl TypeStruct {
         name.to_case(Case::Pascal)
     }
 
+    /// Borrows the values of fields with owned types, e.g. `&'a str` instead of `String`
+    pub(crate) fn borrow_values(&mut self) {
+        for field in self
+            .fields
+            .iter_mut()
+            .filter(|field| field.borrowed && !field.is_array)
+        {
+            if let Some(data_type) = field.data_type.borrowed("'a ") {
+                field.data_type = data_type;
+            }
+        }
+    }
+
+    /// Whether the struct borrows some of its fields and takes a lifetime
+    pub(crate) fn has_lifetime(&self) -> bool {
+        self.fields.iter().any(StructField::is_borrowed)
+    }
+
     pub(crate) fn data_type(&self) -> DataType {
-        DataType(self.name())
+        if self.has_lifetime() {
+            DataType(format!("{}<'_>", self.name()))
+        } else {
+            DataType(self.name())
+        }
     }
 
     pub(crate) fn to_pg_query_slice(&self, var_name: &syn::Ident) -> TokenStream {
@@ -279,8 +390,39 @@ impl TypeStruct {
         if self.fields.len() == 0 {
             quote! {}
         } else {
-            let ident_struct = self.data_type();
-            let fields = self.fields.iter().collect::<Vec<_>>();
+            let ident_struct = DataType(self.name());
+            // params are never read from rows, so they have no column index. Rows with
+            // embedded structs are always read by index, as column names may repeat.
+            let has_embed = self.fields.iter().any(|field| field.embed.is_some());
+            let by_index = (self.decode_by_index || has_embed)
+                && !matches!(self.struct_type, StructType::Params);
+            // embedded structs take up one column per field
+            let mut column = 0;
+            let fields = self
+                .fields
+                .iter()
+                .map(|field| {
+                    let index = Literal::usize_unsuffixed(column);
+                    column += field.embed.unwrap_or(1);
+                    if by_index && field.embed.is_none() {
+                        quote! { #[sqlc(index = #index)] #field }
+                    } else {
+                        quote! { #field }
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // borrowed params can only be written, not decoded or deserialized
+            if self.has_lifetime() {
+                return quote! {
+                    #[derive(Clone, Debug, PartialEq)]
+                    #[cfg_attr(feature = "serde_support", derive(serde::Serialize))]
+                    #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                    pub(crate) struct #ident_struct<'a> {
+                        #(#fields),*
+                    }
+                };
+            }
 
             quote! {
                 #[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
@@ -304,6 +446,10 @@ impl ToTokens for TypeStruct {
 mod tests {
     use super::*;
 
+    fn pretty(tokens: TokenStream) -> String {
+        prettyplease::unparse(&syn::parse_file(&tokens.to_string()).unwrap())
+    }
+
     fn create_struct_field(
         name: Option<&str>,
         number: Option<i32>,
@@ -421,4 +567,161 @@ mod tests {
             .to_string()
         )
     }
+
+    #[test]
+    fn test_generate_code_by_index() {
+        let fields = vec![
+            create_struct_field(Some("id"), None, None, None, Some(true)),
+            create_struct_field(Some("author_id"), Some(1), None, None, None),
+        ];
+        let mut type_struct = create_type_struct(None, Some(StructType::Row), Some(fields));
+        type_struct.decode_by_index = true;
+
+        assert_eq!(
+            pretty(type_struct.generate_code()),
+            pretty(quote! {
+                #[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                pub(crate) struct StructNameRow {
+                    #[sqlc(index = 0)]
+                    pub id: i32,
+                    #[sqlc(index = 1)]
+                    pub author_id: Option<i32>
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_generate_code_embedded() {
+        let mut author = create_type_struct(
+            Some("author"),
+            None,
+            Some(vec![
+                create_struct_field(Some("id"), None, None, None, Some(true)),
+                create_struct_field(Some("name"), Some(1), None, None, Some(true)),
+            ]),
+        );
+        author.table = Some(plugin::Identifier {
+            schema: "public".to_string(),
+            name: "authors".to_string(),
+            ..Default::default()
+        });
+
+        let columns = vec![
+            plugin::Column {
+                name: "authors".to_string(),
+                embed_table: Some(plugin::Identifier {
+                    name: "authors".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            plugin::Column {
+                name: "title".to_string(),
+                not_null: true,
+                r#type: Some(plugin::Identifier {
+                    name: "text".to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ];
+        let type_struct =
+            TypeStruct::from_columns("get_book", &columns, &[], "public", &[], &[author.clone()])
+                .unwrap();
+
+        assert_eq!(
+            pretty(type_struct.generate_code()),
+            pretty(quote! {
+                #[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                pub(crate) struct GetBookRow {
+                    #[sqlc(embed)]
+                    pub author: Author,
+                    #[sqlc(index = 2)]
+                    pub title: String
+                }
+            })
+        );
+
+        let missing = TypeStruct::from_columns("get_book", &columns, &[], "public", &[], &[]);
+        assert_eq!(
+            missing.unwrap_err().kind,
+            ErrorKind::MissingEmbedTable("authors".to_string())
+        );
+    }
+
+    #[test]
+    fn test_generate_code_embedded_indexes() {
+        let table_struct = |name: &str, table: &str, fields: &[&str]| {
+            let fields = fields
+                .iter()
+                .map(|field| create_struct_field(Some(field), None, None, None, Some(true)))
+                .collect();
+            let mut type_struct = create_type_struct(Some(name), None, Some(fields));
+            type_struct.table = Some(plugin::Identifier {
+                schema: "public".to_string(),
+                name: table.to_string(),
+                ..Default::default()
+            });
+            type_struct
+        };
+        let author = table_struct("author", "authors", &["id", "name"]);
+        let publisher = table_struct("publisher", "publishers", &["id", "name", "country"]);
+
+        let column = |name: &str, embed_table: Option<&str>| plugin::Column {
+            name: name.to_string(),
+            not_null: true,
+            r#type: Some(plugin::Identifier {
+                name: "int4".to_string(),
+                ..Default::default()
+            }),
+            embed_table: embed_table.map(|table| plugin::Identifier {
+                name: table.to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let columns = vec![
+            column("id", None),
+            column("authors", Some("authors")),
+            column("pages", None),
+            column("publishers", Some("publishers")),
+            column("year", None),
+        ];
+        let type_struct = TypeStruct::from_columns(
+            "get_book",
+            &columns,
+            &[],
+            "public",
+            &[],
+            &[author, publisher],
+        )
+        .unwrap();
+
+        // every embedded struct moves the following indexes by its number of fields
+        assert_eq!(
+            pretty(type_struct.generate_code()),
+            pretty(quote! {
+                #[derive(Clone, Debug, sqlc_core::FromPostgresRow, PartialEq)]
+                #[cfg_attr(feature = "serde_support", derive(serde::Serialize, serde::Deserialize))]
+                #[cfg_attr(feature = "hash", derive(Eq, Hash))]
+                pub(crate) struct GetBookRow {
+                    #[sqlc(index = 0)]
+                    pub id: i32,
+                    #[sqlc(embed)]
+                    pub author: Author,
+                    #[sqlc(index = 3)]
+                    pub pages: i32,
+                    #[sqlc(embed)]
+                    pub publisher: Publisher,
+                    #[sqlc(index = 7)]
+                    pub year: i32
+                }
+            })
+        );
+    }
 }
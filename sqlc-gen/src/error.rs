@@ -0,0 +1,105 @@
+use sqlc_sqlc_community_neoeinstein_prost::plugin;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ErrorKind {
+    #[error("generate request has no {0}")]
+    MissingRequestField(&'static str),
+
+    #[error("invalid codegen options: {0}")]
+    InvalidOptions(String),
+
+    #[error("invalid query annotation `{0}`")]
+    InvalidCommand(String),
+
+    #[error("table has no name")]
+    MissingTableName,
+
+    #[error("parameter ${0} has no column")]
+    MissingParamColumn(i32),
+
+    #[error("column `{0}` has no type")]
+    MissingColumnType(String),
+
+    #[error("embedded table `{0}` has no struct")]
+    MissingEmbedTable(String),
+
+    #[error("{0}")]
+    InvalidQuery(String),
+
+    #[error("generated code could not be parsed: {0}")]
+    InvalidCode(String),
+}
+
+/// Error reported back to sqlc, pointing at the query that caused it when known
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub query: Option<String>,
+    pub filename: Option<String>,
+    pub kind: ErrorKind,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            query: None,
+            filename: None,
+            kind,
+        }
+    }
+
+    pub fn with_query(mut self, query: &plugin::Query) -> Self {
+        self.query = Some(query.name.clone());
+        if !query.filename.is_empty() {
+            self.filename = Some(query.filename.clone());
+        }
+        self
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(filename) = &self.filename {
+            write!(f, "{filename}: ")?;
+        }
+        if let Some(query) = &self.query {
+            write!(f, "query `{query}`: ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let query = plugin::Query {
+            name: "GetAuthor".to_string(),
+            filename: "query.sql".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Error::new(ErrorKind::InvalidCommand(":two".to_string()))
+                .with_query(&query)
+                .to_string(),
+            "query.sql: query `GetAuthor`: invalid query annotation `:two`"
+        );
+        assert_eq!(
+            Error::new(ErrorKind::MissingRequestField("catalog")).to_string(),
+            "generate request has no catalog"
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use prost::Message;
 use quote::ToTokens;
 use sqlc_sqlc_community_neoeinstein_prost::plugin;
+use std::convert::TryFrom;
 use std::io;
 use std::io::prelude::*;
 
 pub mod codegen;
+pub mod error;
 
 pub fn deserialize_codegen_request(
     buf: &[u8],
@@ -26,12 +28,12 @@ pub fn create_codegen_response(content: &str) -> plugin::GenerateResponse {
     resp
 }
 
-pub fn generate_rust_code(req: plugin::GenerateRequest) -> String {
-    let code_partials: codegen::CodePartials = req.into();
+pub fn generate_rust_code(req: plugin::GenerateRequest) -> Result<String, Vec<error::Error>> {
+    let code_partials = codegen::CodePartials::try_from(req)?;
     let tokens = code_partials.to_token_stream();
-    let syntax_tree = syn::parse_file(tokens.to_string().as_str()).unwrap();
-    return prettyplease::unparse(&syntax_tree);
-    // return tokens.to_string();
+    let syntax_tree = syn::parse_file(tokens.to_string().as_str())
+        .map_err(|e| vec![error::ErrorKind::InvalidCode(e.to_string()).into()])?;
+    return Ok(prettyplease::unparse(&syntax_tree));
 }
 
 fn main() -> Result<(), prost::DecodeError> {
@@ -42,7 +44,16 @@ fn main() -> Result<(), prost::DecodeError> {
 
     let req = deserialize_codegen_request(&buffer)?;
 
-    let out = generate_rust_code(req);
+    let out = match generate_rust_code(req) {
+        Ok(out) => out,
+        Err(errors) => {
+            // sqlc shows the plugin's stderr when it exits with a non-zero code
+            for e in &errors {
+                eprintln!("{e}");
+            }
+            std::process::exit(1);
+        }
+    };
 
     let resp = create_codegen_response(&out);
     let out = serialize_codegen_response(&resp);